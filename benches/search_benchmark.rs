@@ -1,28 +1,30 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use otus::{
     board::Board,
+    game::PositionHistory,
     hashing::TranspTable,
     search::{
-        eval::{smart_eval},
+        eval::smart_eval,
         minimax::{search_alpha_beta, search_minimax, search_minimax_cached},
     },
 };
 
 pub fn minimax_cached(c: &mut Criterion) {
-    let mut board = Board::default();
+    let board = Board::default();
+    let history = PositionHistory::from_board(&board);
 
     c.bench_function("minimax_cached", |b| {
         b.iter(|| {
-            let mut transp_table = TranspTable::new(1 << 20);
-            search_minimax_cached(&mut board, 4, smart_eval, &mut transp_table)
+            let mut transp_table = TranspTable::new(16);
+            search_minimax_cached(&board, 4, smart_eval, &mut transp_table, &history)
         });
     });
 }
 
 pub fn minimax_benchmark(c: &mut Criterion) {
-    let mut board = Board::default();
+    let board = Board::default();
     c.bench_function("minimax", |b| {
-        b.iter(|| search_minimax(&mut board, 3, smart_eval))
+        b.iter(|| search_minimax(&board, 3, smart_eval))
     });
 }
 //baseline 194ms
@@ -32,9 +34,9 @@ pub fn minimax_benchmark(c: &mut Criterion) {
 //eliminating heap allocations for pseudo move generation -> 5ms
 
 pub fn minimax_benchmark_big(c: &mut Criterion) {
-    let mut board = Board::default();
+    let board = Board::default();
     c.bench_function("minimax_big", |b| {
-        b.iter(|| search_minimax(&mut board, 4, smart_eval))
+        b.iter(|| search_minimax(&board, 4, smart_eval))
     });
 }
 //baseline (after above optimizations) 207ms
@@ -42,10 +44,8 @@ pub fn minimax_benchmark_big(c: &mut Criterion) {
 //other optimizations: 80ms
 
 pub fn alpha_beta_benchmark(c: &mut Criterion) {
-    let mut board = Board::default();
-    c.bench_function("alpha_beta", |b| {
-        b.iter(|| search_alpha_beta(&mut board, 4))
-    });
+    let board = Board::default();
+    c.bench_function("alpha_beta", |b| b.iter(|| search_alpha_beta(&board, 4)));
 }
 
 criterion_group!(
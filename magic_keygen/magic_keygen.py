@@ -0,0 +1,67 @@
+import secrets
+
+# Finds magic numbers for rook and bishop attack lookups, see src/board/attacks.rs
+# Square index = rank * 8 + file, a1 = 0
+
+ROOK_DIRS = [(0, 1), (1, 0), (0, -1), (-1, 0)]
+BISHOP_DIRS = [(1, 1), (1, -1), (-1, -1), (-1, 1)]
+MASK64 = (1 << 64) - 1
+
+
+def on_board(file, rank):
+    return 0 <= file < 8 and 0 <= rank < 8
+
+
+def relevant_occupancy_mask(square, dirs):
+    mask = 0
+    for df, dr in dirs:
+        f, r = square % 8 + df, square // 8 + dr
+        while on_board(f + df, r + dr):
+            mask |= 1 << (r * 8 + f)
+            f, r = f + df, r + dr
+    return mask
+
+
+def slider_attacks(square, occupancy, dirs):
+    attacks = 0
+    for df, dr in dirs:
+        f, r = square % 8 + df, square // 8 + dr
+        while on_board(f, r):
+            bit = 1 << (r * 8 + f)
+            attacks |= bit
+            if occupancy & bit:
+                break
+            f, r = f + df, r + dr
+    return attacks
+
+
+def find_magic(square, dirs):
+    mask = relevant_occupancy_mask(square, dirs)
+    bits = bin(mask).count("1")
+    shift = 64 - bits
+    occupancies = []
+    subset = 0
+    while True:
+        occupancies.append(subset)
+        subset = (subset - mask) & mask
+        if subset == 0:
+            break
+    reference = [slider_attacks(square, occ, dirs) for occ in occupancies]
+    while True:
+        magic = secrets.randbits(64) & secrets.randbits(64) & secrets.randbits(64)
+        if bin((mask * magic) & 0xFF00000000000000).count("1") < 6:
+            continue
+        table = {}
+        for occ, attacks in zip(occupancies, reference):
+            index = ((occ * magic) & MASK64) >> shift
+            if table.setdefault(index, attacks) != attacks:
+                break
+        else:
+            return magic
+
+
+for name, dirs in [("ROOK_MAGICS", ROOK_DIRS), ("BISHOP_MAGICS", BISHOP_DIRS)]:
+    print(f"pub const {name}: [u64; 64] = [")
+    for square in range(64):
+        print(f"    0x{find_magic(square, dirs):016X},")
+    print("];")
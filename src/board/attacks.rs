@@ -0,0 +1,194 @@
+use std::sync::OnceLock;
+
+use super::{
+    bitboard::Bitboard,
+    magic_numbers::{BISHOP_MAGICS, ROOK_MAGICS},
+    models::{Color, Piece, PieceType, Square},
+    move_checking::square_utils::{
+        pos_plus, DirIter, KnightHopIter, RayIter, BISHOP_DIRS, ROOK_DIRS,
+    },
+};
+
+/*
+Precomputed attack sets for all pieces.
+Sliding attacks use "fancy" magic bitboards: the relevant blockers of a square are multiplied by a magic number,
+and the top bits of the product index into a shared attack table.
+The magic numbers are generated by magic_keygen/magic_keygen.py, the tables are filled on first use.
+*/
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Magic {
+    mask: u64, // relevant occupancy, excludes the board edge at the end of each ray
+    magic: u64,
+    shift: u32,
+    offset: usize, // start of this square's slice in AttackTables::slider_attacks
+}
+
+impl Magic {
+    fn index(&self, occupancy: Bitboard) -> usize {
+        let blockers = occupancy.0 & self.mask;
+        self.offset + (blockers.wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct AttackTables {
+    knight: [Bitboard; 64],
+    king: [Bitboard; 64],
+    pawn: [[Bitboard; 64]; 2],
+    rook_magics: [Magic; 64],
+    bishop_magics: [Magic; 64],
+    slider_attacks: Vec<Bitboard>,
+}
+
+fn tables() -> &'static AttackTables {
+    static TABLES: OnceLock<AttackTables> = OnceLock::new();
+    TABLES.get_or_init(AttackTables::new)
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    tables().knight[square.to_index()]
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    tables().king[square.to_index()]
+}
+
+// squares attacked by a pawn of the given color standing on square
+pub fn pawn_attacks(color: Color, square: Square) -> Bitboard {
+    tables().pawn[color as usize][square.to_index()]
+}
+
+pub fn rook_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    let tables = tables();
+    tables.slider_attacks[tables.rook_magics[square.to_index()].index(occupancy)]
+}
+
+pub fn bishop_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    let tables = tables();
+    tables.slider_attacks[tables.bishop_magics[square.to_index()].index(occupancy)]
+}
+
+pub fn queen_attacks(square: Square, occupancy: Bitboard) -> Bitboard {
+    rook_attacks(square, occupancy) | bishop_attacks(square, occupancy)
+}
+
+// squares attacked by the given piece standing on square, pawn pushes are not attacks
+pub fn piece_attacks(piece: Piece, square: Square, occupancy: Bitboard) -> Bitboard {
+    match piece.0 {
+        PieceType::Pawn => pawn_attacks(piece.1, square),
+        PieceType::Knight => knight_attacks(square),
+        PieceType::Bishop => bishop_attacks(square, occupancy),
+        PieceType::Rook => rook_attacks(square, occupancy),
+        PieceType::Queen => queen_attacks(square, occupancy),
+        PieceType::King => king_attacks(square),
+    }
+}
+
+// Slow reference implementation, only used to fill the tables
+fn slider_attacks_slow(square: Square, occupancy: u64, dirs: &[(i8, i8)]) -> u64 {
+    let mut attacks = 0;
+    for dir in dirs {
+        for pos in RayIter::new(square, *dir) {
+            let bit = 1 << pos.to_index();
+            attacks |= bit;
+            if occupancy & bit != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+// All squares on the rays except the last one, a piece on the edge can never block anything
+fn relevant_occupancy_mask(square: Square, dirs: &[(i8, i8)]) -> u64 {
+    let mut mask = 0;
+    for dir in dirs {
+        for pos in RayIter::new(square, *dir) {
+            if pos_plus(pos, *dir).is_none() {
+                break;
+            }
+            mask |= 1 << pos.to_index();
+        }
+    }
+    mask
+}
+
+fn init_magic(
+    square: Square,
+    dirs: &[(i8, i8)],
+    magic: u64,
+    attack_table: &mut Vec<Bitboard>,
+) -> Magic {
+    let mask = relevant_occupancy_mask(square, dirs);
+    let bits = mask.count_ones();
+    let entry = Magic {
+        mask,
+        magic,
+        shift: 64 - bits,
+        offset: attack_table.len(),
+    };
+    attack_table.resize(entry.offset + (1 << bits), Bitboard::EMPTY);
+    // enumerate all subsets of mask (carry-rippler trick)
+    let mut occupancy: u64 = 0;
+    loop {
+        let attacks = Bitboard(slider_attacks_slow(square, occupancy, dirs));
+        let index = entry.index(Bitboard(occupancy));
+        // destructive collisions mean the magic number is wrong
+        debug_assert!(attack_table[index].is_empty() || attack_table[index] == attacks);
+        attack_table[index] = attacks;
+        occupancy = occupancy.wrapping_sub(mask) & mask;
+        if occupancy == 0 {
+            break;
+        }
+    }
+    entry
+}
+
+impl AttackTables {
+    fn new() -> AttackTables {
+        let mut knight = [Bitboard::EMPTY; 64];
+        let mut king = [Bitboard::EMPTY; 64];
+        let mut pawn = [[Bitboard::EMPTY; 64]; 2];
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            for pos in KnightHopIter::new(square) {
+                knight[index] |= Bitboard::from_square(pos);
+            }
+            for pos in DirIter::all().filter_map(|dir| pos_plus(square, dir)) {
+                king[index] |= Bitboard::from_square(pos);
+            }
+            for (color, forward) in [(Color::White, 1), (Color::Black, -1)] {
+                for pos in [(-1, forward), (1, forward)]
+                    .iter()
+                    .filter_map(|step| pos_plus(square, *step))
+                {
+                    pawn[color as usize][index] |= Bitboard::from_square(pos);
+                }
+            }
+        }
+
+        let mut slider_attacks = Vec::new();
+        let mut rook_magics = [Magic::default(); 64];
+        let mut bishop_magics = [Magic::default(); 64];
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            rook_magics[index] =
+                init_magic(square, &ROOK_DIRS, ROOK_MAGICS[index], &mut slider_attacks);
+            bishop_magics[index] = init_magic(
+                square,
+                &BISHOP_DIRS,
+                BISHOP_MAGICS[index],
+                &mut slider_attacks,
+            );
+        }
+
+        AttackTables {
+            knight,
+            king,
+            pawn,
+            rook_magics,
+            bishop_magics,
+            slider_attacks,
+        }
+    }
+}
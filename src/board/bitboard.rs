@@ -0,0 +1,126 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use super::models::{File, Rank, Square};
+
+// Set of squares, bit i corresponds to square index i (a1 = 0, b1 = 1, ..., h8 = 63)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Bitboard(pub u64);
+
+impl Square {
+    pub fn to_index(self) -> usize {
+        self.1 as usize * 8 + self.0 as usize
+    }
+
+    pub fn from_index(index: usize) -> Square {
+        Square(
+            File::from_i8((index % 8) as i8).unwrap(),
+            Rank::from_i8((index / 8) as i8).unwrap(),
+        )
+    }
+}
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+
+    pub fn from_square(square: Square) -> Bitboard {
+        Bitboard(1 << square.to_index())
+    }
+
+    pub fn contains(self, square: Square) -> bool {
+        self.0 & (1 << square.to_index()) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    // least significant square, i.e. lowest rank first, then lowest file
+    pub fn lsb(self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Square::from_index(self.0.trailing_zeros() as usize))
+        }
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Self::Output {
+        Bitboard(!self.0)
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+
+// Iterates through the squares of a bitboard in ascending index order
+pub struct BitboardIter {
+    remaining: u64,
+}
+
+impl Iterator for BitboardIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let index = self.remaining.trailing_zeros() as usize;
+        self.remaining &= self.remaining - 1; // clear lowest set bit
+        Some(Square::from_index(index))
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = BitboardIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        BitboardIter { remaining: self.0 }
+    }
+}
@@ -1,127 +1,75 @@
 use super::{
+    attacks::{bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rook_attacks},
+    bitboard::{Bitboard, BitboardIter},
+    model_utils::ColorProps,
     models::{Color, Piece, PieceType, Square},
-    move_checking::square_utils::{pos_plus, DirIter, KnightHopIter, RayIter, SquareIter},
     Board,
 };
 
 pub struct PlayerPieceIter<'a> {
     board: &'a Board,
-    player: Color,
-    square_iter: SquareIter,
+    square_iter: BitboardIter,
 }
 
 impl<'a> PlayerPieceIter<'a> {
     pub fn new(board: &'a Board, player: Color) -> PlayerPieceIter<'a> {
         PlayerPieceIter {
             board,
-            player,
-            square_iter: SquareIter::new(),
+            square_iter: board.color_pieces(player).into_iter(),
         }
     }
 }
 
 // Iterates through a player's pieces
-impl<'a> Iterator for PlayerPieceIter<'a> {
+impl Iterator for PlayerPieceIter<'_> {
     type Item = (PieceType, Square);
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(square) = self.square_iter.next() {
-                if let Some(Piece(piece, owner)) = self.board.get_piece_at(square) {
-                    if owner == self.player {
-                        return Some((piece, square));
-                    }
-                }
-            } else {
-                return None;
-            }
-        }
+        let square = self.square_iter.next()?;
+        let Piece(piece, _) = self
+            .board
+            .get_piece_at(square)
+            .expect("color bitboard out of sync with squares");
+        Some((piece, square))
     }
 }
 
 // Is square under attack from opponent of active player
 pub fn is_square_attacked(board: &Board, target: Square) -> bool {
-    let active_player = board.active_player;
-    let pawn_dir = match board.active_player {
-        Color::White => 1,
-        Color::Black => -1,
-    };
-    let pawn_attack_dirs = [(1, pawn_dir), (-1, pawn_dir)];
-
-    // diagonal moves
-    for dir in DirIter::bishop() {
-        let pos = match pos_plus(target, dir) {
-            Some(pos) => pos,
-            None => continue,
-        };
-        if let Some(Piece(piece, owner)) = board.get_piece_at(pos) {
-            if owner == active_player {
-                continue; //this direction is safe, attacks blocked by friendly piece
-            }
-            if pawn_attack_dirs.contains(&dir) && piece == PieceType::Pawn {
-                return true;
-            }
-            match piece {
-                PieceType::Bishop => return true,
-                PieceType::Queen => return true,
-                PieceType::King => return true,
-                _ => continue, // enemy piece blocks diagonal attacks
-            }
-            // cast ray to detect distant attackers
-        };
-        for pos in RayIter::new(pos, dir) {
-            if let Some(Piece(piece, owner)) = board.get_piece_at(pos) {
-                if owner == active_player {
-                    break; //attacks blocked by friendly piece
-                }
-                match piece {
-                    PieceType::Bishop => return true,
-                    PieceType::Queen => return true,
-                    _ => break, // enemy piece blocks sliding attacks
-                }
-            }
-        }
+    let opponent = board.active_player.opponent();
+    let occupancy = board.occupancy();
+    // a piece on target would attack the attacker's square with the same pattern
+    let pawns = board.piece_bb(Piece(PieceType::Pawn, opponent));
+    if !(pawn_attacks(board.active_player, target) & pawns).is_empty() {
+        return true;
     }
-
-    // horizontal and vertical moves
-    for dir in DirIter::rook() {
-        let pos = match pos_plus(target, dir) {
-            Some(pos) => pos,
-            None => continue,
-        };
-        if let Some(Piece(piece, owner)) = board.get_piece_at(pos) {
-            if owner == active_player {
-                continue; //this direction is safe, attacks blocked by friendly piece
-            }
-            match piece {
-                PieceType::Rook => return true,
-                PieceType::Queen => return true,
-                PieceType::King => return true,
-                _ => continue, // enemy piece blocks horizontal and vertical attacks
-            }
-            // cast ray to detect distant attackers
-        };
-        for pos in RayIter::new(pos, dir) {
-            if let Some(Piece(piece, owner)) = board.get_piece_at(pos) {
-                if owner == active_player {
-                    break; //attacks blocked by friendly piece
-                }
-                match piece {
-                    PieceType::Rook => return true,
-                    PieceType::Queen => return true,
-                    _ => break, // enemy piece blocks sliding attacks
-                }
-            }
-        }
+    let knights = board.piece_bb(Piece(PieceType::Knight, opponent));
+    if !(knight_attacks(target) & knights).is_empty() {
+        return true;
     }
-
-    // knight moves
-    for pos in KnightHopIter::new(target) {
-        if let Some(Piece(PieceType::Knight, owner)) = board.get_piece_at(pos) {
-            if owner != active_player {
-                return true;
-            }
-        }
+    let king = board.piece_bb(Piece(PieceType::King, opponent));
+    if !(king_attacks(target) & king).is_empty() {
+        return true;
+    }
+    let queens = board.piece_bb(Piece(PieceType::Queen, opponent));
+    let diagonal_sliders = board.piece_bb(Piece(PieceType::Bishop, opponent)) | queens;
+    if !(bishop_attacks(target, occupancy) & diagonal_sliders).is_empty() {
+        return true;
     }
-    false
+    let straight_sliders = board.piece_bb(Piece(PieceType::Rook, opponent)) | queens;
+    !(rook_attacks(target, occupancy) & straight_sliders).is_empty()
+}
+
+// Pieces of both colors attacking target, sliders are blocked by occupancy instead of the board's pieces
+pub fn attackers_to(board: &Board, target: Square, occupancy: Bitboard) -> Bitboard {
+    let queens = board.pieces(PieceType::Queen);
+    let diagonal_sliders = board.pieces(PieceType::Bishop) | queens;
+    let straight_sliders = board.pieces(PieceType::Rook) | queens;
+    (pawn_attacks(Color::Black, target) & board.piece_bb(Piece(PieceType::Pawn, Color::White)))
+        | (pawn_attacks(Color::White, target)
+            & board.piece_bb(Piece(PieceType::Pawn, Color::Black)))
+        | (knight_attacks(target) & board.pieces(PieceType::Knight))
+        | (king_attacks(target) & board.pieces(PieceType::King))
+        | (bishop_attacks(target, occupancy) & diagonal_sliders)
+        | (rook_attacks(target, occupancy) & straight_sliders)
 }
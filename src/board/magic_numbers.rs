@@ -0,0 +1,132 @@
+pub const ROOK_MAGICS: [u64; 64] = [
+    0x008001C000601480,
+    0x2040002000100040,
+    0x0180098020009002,
+    0x0280080080900006,
+    0x0880040002810800,
+    0x0A00020010084401,
+    0x210010A1000A0044,
+    0x8200008021020044,
+    0x9008800780400820,
+    0x0000400040201004,
+    0x9722002842008010,
+    0x8015000810002100,
+    0x020B000802050050,
+    0xA925000204010008,
+    0xC582000A00093884,
+    0x8016002051040082,
+    0x0801888001400221,
+    0x1800414000201002,
+    0x0000838020041000,
+    0x1950010011000820,
+    0x0114808008000400,
+    0x1801010002080400,
+    0x0208040002015008,
+    0x0008120000510084,
+    0x4C00400180008020,
+    0x44A0500240002000,
+    0x0000200080100082,
+    0x0000100100090020,
+    0x0008000880040080,
+    0x4002000200040810,
+    0x0802001A00081407,
+    0x0055040A00209041,
+    0x0200400020800082,
+    0x6020201000404000,
+    0x0800108202002440,
+    0x0020801000800802,
+    0x0010220222003088,
+    0x2000800400800200,
+    0x20310004A1000200,
+    0x4020224102000A84,
+    0x0880400080248000,
+    0x0010200050004000,
+    0x0128402001010010,
+    0x0003001001250008,
+    0xC400080005010010,
+    0x0A06000810020004,
+    0x0000812208040010,
+    0x0A04211080420004,
+    0x0040800310204300,
+    0x0822883104400100,
+    0x428D004010200100,
+    0x1001002010010900,
+    0x1080800800040080,
+    0x0002000408100200,
+    0x0144825001080400,
+    0x8080040071008200,
+    0x0080010810402081,
+    0x0820804000201901,
+    0x000020000D004011,
+    0x8001002006081001,
+    0x090E002010080402,
+    0x090500040006A81D,
+    0x800028011000D204,
+    0x0281001840220081,
+];
+pub const BISHOP_MAGICS: [u64; 64] = [
+    0x080481222C040280,
+    0x0091020084128004,
+    0x0010044880200020,
+    0x00A20A0202040200,
+    0x108410444C800000,
+    0x040A020320040C14,
+    0x6024012148205D30,
+    0x8005260050080800,
+    0x000A095004008400,
+    0x0200900188010040,
+    0x4000040102120118,
+    0x9202144100200000,
+    0x8580011040808040,
+    0x8900609004200402,
+    0x0008C42208028810,
+    0x0100088208014450,
+    0x0010022024015800,
+    0x0210022081020080,
+    0x6008004420242200,
+    0x04A0250202004000,
+    0x0801026820081080,
+    0xA00A000101210140,
+    0x2804200082013008,
+    0x0012082900988400,
+    0x04200810205A1480,
+    0x102802000802080D,
+    0x0A280C1208022020,
+    0x0000808228020002,
+    0x0015040036002108,
+    0x002902010D004102,
+    0x0031040020440480,
+    0x2005104C2A004400,
+    0x8488201080040400,
+    0x4204102200090200,
+    0x0204A80800740020,
+    0x2000020080080080,
+    0x2040404040040100,
+    0x8800A10201590080,
+    0x000128008C190400,
+    0x04A85600C0008840,
+    0x000A5002080A2000,
+    0x4441012120023000,
+    0x8008414020805001,
+    0x2020014208808800,
+    0x8844081011041010,
+    0x4482201840800103,
+    0x0090F40810500680,
+    0x040401004A05010C,
+    0x1012110402410000,
+    0x1403084210040002,
+    0x0002010080901404,
+    0x000400002A080008,
+    0xC800008611340108,
+    0x0000210302020400,
+    0x0020600200811000,
+    0x4344100224411080,
+    0xC221802802122000,
+    0x0002008404020202,
+    0x0449000441082110,
+    0x1030440018840440,
+    0x0050008008210440,
+    0x4084866802286200,
+    0x2180081010520040,
+    0x1258202C00902900,
+];
@@ -3,39 +3,55 @@ use std::fmt::{self, Display};
 use crate::board::models::Piece;
 
 use self::{
+    attacks::{pawn_attacks, piece_attacks},
+    bitboard::Bitboard,
     board_utils::PlayerPieceIter,
     model_utils::ColorProps,
     models::{
-        Color, File, GameState, LegalMove, Move, PieceType, PromotionPieceType, Rank, Square,
+        Color, DrawReason, File, GameState, LegalMove, Move, PieceType, PromotionPieceType, Rank,
+        Square,
     },
     move_checking::{
         can_castle_kingside, can_castle_queenside, get_legal_move_from_pseudolegal_move,
-        is_king_in_check, is_move_legal,
-        square_utils::{pos_plus, DirIter, KnightHopIter, RayIter},
+        is_king_in_check, is_move_legal, square_utils::pos_plus,
     },
 };
 
+pub mod attacks;
+pub mod bitboard;
 pub mod board_utils;
+mod magic_numbers;
 pub mod model_utils;
 pub mod models;
 pub mod move_checking;
+pub mod san;
+mod see;
 
+#[cfg(test)]
+pub mod test_utils;
 #[cfg(test)]
 mod tests;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Board {
     squares: [[Option<Piece>; 8]; 8],
+    // kept in sync with squares, indexed by PieceType and Color respectively
+    piece_bbs: [Bitboard; 6],
+    color_bbs: [Bitboard; 2],
     pub active_player: Color,
     pub castling_rights: u8, // KQkq
     pub en_passant_target: Option<Square>,
+    pub halfmove_clock: u16, // plies since the last capture or pawn move
+    pub fullmove_number: u16,
 }
 
-impl Board {
-    pub fn default() -> Board {
+impl Default for Board {
+    fn default() -> Board {
         Self::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
     }
+}
 
+impl Board {
     fn get_piece(&self, file: File, rank: Rank) -> Option<Piece> {
         self.squares[rank as usize][file as usize]
     }
@@ -44,16 +60,47 @@ impl Board {
         self.get_piece(pos.0, pos.1)
     }
 
+    // All pieces of the given type, regardless of color
+    pub fn pieces(&self, piece: PieceType) -> Bitboard {
+        self.piece_bbs[piece as usize]
+    }
+
+    // All pieces of the given color
+    pub fn color_pieces(&self, color: Color) -> Bitboard {
+        self.color_bbs[color as usize]
+    }
+
+    pub fn piece_bb(&self, piece: Piece) -> Bitboard {
+        self.piece_bbs[piece.0 as usize] & self.color_bbs[piece.1 as usize]
+    }
+
+    pub fn occupancy(&self) -> Bitboard {
+        self.color_bbs[0] | self.color_bbs[1]
+    }
+
+    // toggles the piece on the square in the bitboards, mailbox must be updated by the caller
+    fn toggle_bitboards(&mut self, pos: Square, piece: Piece) {
+        let bb = Bitboard::from_square(pos);
+        self.piece_bbs[piece.0 as usize] ^= bb;
+        self.color_bbs[piece.1 as usize] ^= bb;
+    }
+
     fn set_piece_at(&mut self, pos: Square, piece: Piece) {
-        self.squares[pos.1 as usize][pos.0 as usize] = Some(piece);
+        self.set_square(pos, Some(piece));
     }
 
     fn set_square(&mut self, pos: Square, piece: Option<Piece>) {
+        if let Some(old_piece) = self.get_piece_at(pos) {
+            self.toggle_bitboards(pos, old_piece);
+        }
+        if let Some(new_piece) = piece {
+            self.toggle_bitboards(pos, new_piece);
+        }
         self.squares[pos.1 as usize][pos.0 as usize] = piece;
     }
 
     fn clear_square(&mut self, pos: Square) {
-        self.squares[pos.1 as usize][pos.0 as usize] = None;
+        self.set_square(pos, None);
     }
 
     fn squares_from_fen(fen_squares: &str) -> Result<[[Option<Piece>; 8]; 8], String> {
@@ -130,6 +177,17 @@ impl Board {
             return Err("Expected 6 parts in fen".to_string());
         }
         let squares = Self::squares_from_fen(parts[0])?;
+        let mut piece_bbs = [Bitboard::EMPTY; 6];
+        let mut color_bbs = [Bitboard::EMPTY; 2];
+        for (rank, row) in squares.iter().enumerate() {
+            for (file, piece) in row.iter().enumerate() {
+                if let Some(Piece(piece_type, color)) = piece {
+                    let bb = Bitboard(1 << (rank * 8 + file));
+                    piece_bbs[*piece_type as usize] |= bb;
+                    color_bbs[*color as usize] |= bb;
+                }
+            }
+        }
         let active_player = match parts[1] {
             "w" => Color::White,
             "b" => Color::Black,
@@ -140,11 +198,21 @@ impl Board {
             "-" => None,
             s => Some(Square::from_string(s)?),
         };
+        let halfmove_clock = parts[4]
+            .parse()
+            .map_err(|_| format!("Invalid halfmove clock: {}", parts[4]))?;
+        let fullmove_number = parts[5]
+            .parse()
+            .map_err(|_| format!("Invalid fullmove number: {}", parts[5]))?;
         Ok(Board {
             squares,
+            piece_bbs,
+            color_bbs,
             active_player,
             castling_rights,
             en_passant_target,
+            halfmove_clock,
+            fullmove_number,
         })
     }
 
@@ -212,11 +280,14 @@ impl Board {
             Some(p) => fen.push_str(&p.to_string()),
             None => fen.push('-'),
         }
-        fen.push_str(" 0 1");
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
         fen
     }
 
-    fn has_kingside_castling_rights(&self, color: Color) -> bool {
+    pub fn has_kingside_castling_rights(&self, color: Color) -> bool {
         self.castling_rights
             & match color {
                 Color::White => 0b1000,
@@ -225,7 +296,7 @@ impl Board {
             != 0
     }
 
-    fn has_queenside_castling_rights(&self, color: Color) -> bool {
+    pub fn has_queenside_castling_rights(&self, color: Color) -> bool {
         self.castling_rights
             & match color {
                 Color::White => 0b0100,
@@ -246,7 +317,11 @@ impl Board {
     }
 
     fn try_add_promotion_moves(&self, src: Square, dest: Square, moves: &mut Vec<LegalMove>) {
-        if let Some(LegalMove::Promotion { castle_mask, .. }) = get_legal_move_from_pseudolegal_move(
+        if let Some(LegalMove::Promotion {
+            castle_mask,
+            halfmove_clock,
+            ..
+        }) = get_legal_move_from_pseudolegal_move(
             self,
             &Move::Promotion {
                 src,
@@ -267,83 +342,69 @@ impl Board {
                     promotion: promotion_piece,
                     castle_mask,
                     captured_piece: dst_piece,
+                    halfmove_clock,
+                    en_passant_target: self.en_passant_target,
                 });
             }
         }
     }
 
+    // pseudo-legal destinations of a pawn of the active player, including promotion squares
+    fn pawn_targets(&self, src: Square) -> Bitboard {
+        let active_player = self.active_player;
+        let forward = match active_player {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        let mut capturable = self.color_pieces(active_player.opponent());
+        if let Some(ep_square) = self.en_passant_target {
+            capturable |= Bitboard::from_square(ep_square);
+        }
+        let mut targets = pawn_attacks(active_player, src) & capturable;
+        if let Some(push) = pos_plus(src, (0, forward)) {
+            if self.get_piece_at(push).is_none() {
+                targets |= Bitboard::from_square(push);
+                if src.1 == active_player.pawn_start_rank() {
+                    let double_push = Square(src.0, active_player.double_push_rank());
+                    if self.get_piece_at(double_push).is_none() {
+                        targets |= Bitboard::from_square(double_push);
+                    }
+                }
+            }
+        }
+        targets
+    }
+
+    // pseudo-legal destinations of a non-pawn piece of the active player
+    fn piece_targets(&self, piece: PieceType, src: Square) -> Bitboard {
+        piece_attacks(Piece(piece, self.active_player), src, self.occupancy())
+            & !self.color_pieces(self.active_player)
+    }
+
     pub fn get_legal_moves(&self) -> Vec<LegalMove> {
         let mut legal_moves = Vec::new();
-        let (forward, home_rank, opp_home_rank) = match self.active_player {
-            Color::White => (1, Rank::_2, Rank::_7),
-            Color::Black => (-1, Rank::_7, Rank::_2),
-        };
+        let opp_home_rank = self.active_player.opponent().pawn_start_rank();
         for (piece, src) in PlayerPieceIter::new(self, self.active_player) {
-            let mk_ray = move |dir: (i8, i8)| RayIter::new(src, dir);
             match piece {
-                PieceType::Queen => DirIter::all()
-                    .flat_map(mk_ray)
-                    .filter_map(|dest| {
-                        get_legal_move_from_pseudolegal_move(self, &Move::Normal { src, dest })
-                    })
-                    .for_each(|m| legal_moves.push(m)),
-                PieceType::Rook => DirIter::rook()
-                    .flat_map(mk_ray)
-                    .filter_map(|dest| {
-                        get_legal_move_from_pseudolegal_move(self, &Move::Normal { src, dest })
-                    })
-                    .for_each(|m| legal_moves.push(m)),
-                PieceType::Bishop => DirIter::bishop()
-                    .flat_map(mk_ray)
-                    .filter_map(|dest| {
-                        get_legal_move_from_pseudolegal_move(self, &Move::Normal { src, dest })
-                    })
-                    .for_each(|m| legal_moves.push(m)),
-                PieceType::Knight => KnightHopIter::new(src)
+                PieceType::Pawn if src.1 == opp_home_rank => {
+                    for dest in self.pawn_targets(src) {
+                        self.try_add_promotion_moves(src, dest, &mut legal_moves);
+                    }
+                }
+                PieceType::Pawn => self
+                    .pawn_targets(src)
+                    .into_iter()
                     .filter_map(|dest| {
                         get_legal_move_from_pseudolegal_move(self, &Move::Normal { src, dest })
                     })
                     .for_each(|m| legal_moves.push(m)),
-                PieceType::King => DirIter::all()
-                    .flat_map(|dir| pos_plus(src, dir))
+                _ => self
+                    .piece_targets(piece, src)
+                    .into_iter()
                     .filter_map(|dest| {
                         get_legal_move_from_pseudolegal_move(self, &Move::Normal { src, dest })
                     })
                     .for_each(|m| legal_moves.push(m)),
-                PieceType::Pawn => {
-                    if src.1 == opp_home_rank {
-                        [(0, forward), (-1, forward), (1, forward)]
-                            .iter()
-                            .filter_map(move |step| pos_plus(src, *step))
-                            .for_each(|dest| {
-                                self.try_add_promotion_moves(src, dest, &mut legal_moves);
-                            });
-                    } else if home_rank == src.1 {
-                        //include double pawn push
-                        [(0, forward), (-1, forward), (1, forward), (0, 2 * forward)]
-                            .iter()
-                            .filter_map(move |step| pos_plus(src, *step))
-                            .filter_map(|dest| {
-                                get_legal_move_from_pseudolegal_move(
-                                    self,
-                                    &Move::Normal { src, dest },
-                                )
-                            })
-                            .for_each(|m| legal_moves.push(m));
-                    } else {
-                        //exclude double pawn push
-                        [(0, forward), (-1, forward), (1, forward)]
-                            .iter()
-                            .filter_map(move |step| pos_plus(src, *step))
-                            .filter_map(|dest| {
-                                get_legal_move_from_pseudolegal_move(
-                                    self,
-                                    &Move::Normal { src, dest },
-                                )
-                            })
-                            .for_each(|m| legal_moves.push(m));
-                    }
-                }
             }
         }
         if can_castle_kingside(self) {
@@ -352,6 +413,7 @@ impl Board {
                     Color::White => self.castling_rights & 0b1100,
                     Color::Black => self.castling_rights & 0b0011,
                 },
+                en_passant_target: self.en_passant_target,
             });
         }
         if can_castle_queenside(self) {
@@ -360,76 +422,107 @@ impl Board {
                     Color::White => self.castling_rights & 0b1100,
                     Color::Black => self.castling_rights & 0b0011,
                 },
+                en_passant_target: self.en_passant_target,
             });
         }
         legal_moves
     }
 
+    // Legal captures, en passant captures and queen promotions: the moves that change the material balance.
+    // Used by the quiescence search, which needs them much more often than all legal moves.
+    pub fn get_capture_moves(&self) -> Vec<LegalMove> {
+        let mut captures = Vec::new();
+        let capturable = self.color_pieces(self.active_player.opponent());
+        let opp_home_rank = self.active_player.opponent().pawn_start_rank();
+        for (piece, src) in PlayerPieceIter::new(self, self.active_player) {
+            let targets = match piece {
+                PieceType::Pawn if src.1 == opp_home_rank => {
+                    for dest in self.pawn_targets(src) {
+                        captures.extend(get_legal_move_from_pseudolegal_move(
+                            self,
+                            &Move::Promotion {
+                                src,
+                                dest,
+                                promotion: PromotionPieceType::Queen,
+                            },
+                        ));
+                    }
+                    continue;
+                }
+                PieceType::Pawn => {
+                    let en_passant = self.en_passant_target.map(Bitboard::from_square);
+                    self.pawn_targets(src) & (capturable | en_passant.unwrap_or_default())
+                }
+                _ => self.piece_targets(piece, src) & capturable,
+            };
+            captures.extend(targets.into_iter().filter_map(|dest| {
+                get_legal_move_from_pseudolegal_move(self, &Move::Normal { src, dest })
+            }));
+        }
+        captures
+    }
+
     fn has_legal_moves(&self) -> bool {
-        let (forward, home_rank, opp_home_rank) = match self.active_player {
-            Color::White => (1, Rank::_2, Rank::_7),
-            Color::Black => (-1, Rank::_7, Rank::_2),
-        };
+        let opp_home_rank = self.active_player.opponent().pawn_start_rank();
         for (piece, src) in PlayerPieceIter::new(self, self.active_player) {
-            let mk_ray = move |dir: (i8, i8)| RayIter::new(src, dir);
             if match piece {
-                PieceType::Queen => DirIter::all()
-                    .flat_map(mk_ray)
-                    .any(|dest| is_move_legal(self, &Move::Normal { src, dest })),
-                PieceType::Rook => DirIter::rook()
-                    .flat_map(mk_ray)
-                    .any(|dest| is_move_legal(self, &Move::Normal { src, dest })),
-                PieceType::Bishop => DirIter::bishop()
-                    .flat_map(mk_ray)
-                    .any(|dest| is_move_legal(self, &Move::Normal { src, dest })),
-                PieceType::Knight => KnightHopIter::new(src)
+                PieceType::Pawn if src.1 == opp_home_rank => {
+                    // promotion
+                    self.pawn_targets(src).into_iter().any(|dest| {
+                        is_move_legal(
+                            self,
+                            &Move::Promotion {
+                                src,
+                                dest,
+                                promotion: PromotionPieceType::Queen,
+                            },
+                        )
+                    })
+                }
+                PieceType::Pawn => self
+                    .pawn_targets(src)
+                    .into_iter()
                     .any(|dest| is_move_legal(self, &Move::Normal { src, dest })),
-                PieceType::King => DirIter::all()
-                    .flat_map(|dir| pos_plus(src, dir))
+                _ => self
+                    .piece_targets(piece, src)
+                    .into_iter()
                     .any(|dest| is_move_legal(self, &Move::Normal { src, dest })),
-                PieceType::Pawn => {
-                    if src.1 == opp_home_rank {
-                        // promotion
-                        [(0, forward), (-1, forward), (1, forward)]
-                            .iter()
-                            .filter_map(move |step| pos_plus(src, *step))
-                            .any(|dest| {
-                                is_move_legal(
-                                    self,
-                                    &Move::Promotion {
-                                        src,
-                                        dest,
-                                        promotion: PromotionPieceType::Queen,
-                                    },
-                                )
-                            })
-                    } else if home_rank == src.1 {
-                        //include double pawn push
-                        [(0, forward), (-1, forward), (1, forward), (0, 2 * forward)]
-                            .iter()
-                            .filter_map(move |step| pos_plus(src, *step))
-                            .any(|dest| is_move_legal(self, &Move::Normal { src, dest }))
-                    } else {
-                        //exclude double pawn push
-                        [(0, forward), (-1, forward), (1, forward)]
-                            .iter()
-                            .filter_map(move |step| pos_plus(src, *step))
-                            .any(|dest| is_move_legal(self, &Move::Normal { src, dest }))
-                    }
-                }
             } {
                 return true;
             }
         }
-        if is_move_legal(self, &Move::CastleKingside {}) {
+        if is_move_legal(self, &Move::CastleKingside) {
             return true;
         }
-        if is_move_legal(self, &Move::CastleQueenside {}) {
+        if is_move_legal(self, &Move::CastleQueenside) {
             return true;
         }
         false
     }
 
+    // True if neither side can possibly deliver mate: K vs K, K+minor vs K, or only same-colored bishops
+    pub fn is_insufficient_material(&self) -> bool {
+        const LIGHT_SQUARES: Bitboard = Bitboard(0x55AA_55AA_55AA_55AA);
+        let heavy_pieces = self.pieces(PieceType::Pawn)
+            | self.pieces(PieceType::Rook)
+            | self.pieces(PieceType::Queen);
+        if !heavy_pieces.is_empty() {
+            return false;
+        }
+        let knights = self.pieces(PieceType::Knight);
+        let bishops = self.pieces(PieceType::Bishop);
+        if (knights | bishops).count() <= 1 {
+            return true;
+        }
+        knights.is_empty()
+            && ((bishops & LIGHT_SQUARES).is_empty() || (bishops & !LIGHT_SQUARES).is_empty())
+    }
+
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    // Threefold repetition needs the game history, see game::PositionHistory::get_gamestate
     pub fn get_gamestate(&self) -> GameState {
         if !self.has_legal_moves() {
             if is_king_in_check(self) {
@@ -437,6 +530,10 @@ impl Board {
             } else {
                 GameState::Stalemate
             }
+        } else if self.is_fifty_move_draw() {
+            GameState::Draw(DrawReason::FiftyMoveRule)
+        } else if self.is_insufficient_material() {
+            GameState::Draw(DrawReason::InsufficientMaterial)
         } else {
             GameState::InProgress
         }
@@ -451,6 +548,26 @@ impl Board {
     pub fn make_move(&mut self, move_: &LegalMove) {
         let active_player = self.active_player;
         self.en_passant_target = None;
+        let resets_halfmove_clock = match move_ {
+            LegalMove::Normal {
+                src,
+                captured_piece,
+                ..
+            } => {
+                captured_piece.is_some()
+                    || self.get_piece_at(*src) == Some(Piece(PieceType::Pawn, active_player))
+            }
+            LegalMove::CastleKingside { .. } | LegalMove::CastleQueenside { .. } => false,
+            _ => true,
+        };
+        if resets_halfmove_clock {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock = self.halfmove_clock.saturating_add(1);
+        }
+        if active_player == Color::Black {
+            self.fullmove_number = self.fullmove_number.saturating_add(1);
+        }
         match move_ {
             LegalMove::Normal {
                 src,
@@ -472,7 +589,7 @@ impl Board {
                 self.clear_square(*src);
                 self.castling_rights ^= castle_mask;
             }
-            LegalMove::CastleKingside { castle_mask } => {
+            LegalMove::CastleKingside { castle_mask, .. } => {
                 self.move_piece(
                     active_player.king_home_square(),
                     Square(File::G, active_player.home_rank()),
@@ -483,7 +600,7 @@ impl Board {
                 );
                 self.castling_rights ^= castle_mask;
             }
-            LegalMove::CastleQueenside { castle_mask } => {
+            LegalMove::CastleQueenside { castle_mask, .. } => {
                 self.move_piece(
                     active_player.king_home_square(),
                     Square(File::C, active_player.home_rank()),
@@ -506,7 +623,7 @@ impl Board {
                 self.clear_square(Square(*file, src_rank));
                 self.en_passant_target = Some(Square(*file, target_rank));
             }
-            LegalMove::EnPassantCapture { src, dest } => {
+            LegalMove::EnPassantCapture { src, dest, .. } => {
                 self.move_piece(*src, *dest);
                 self.clear_square(Square(dest.0, active_player.opponent().double_push_rank()));
             }
@@ -514,21 +631,45 @@ impl Board {
         self.active_player = active_player.opponent();
     }
 
-    // TODO: Unmake move currently DOES NOT restore the en passant target
-    // This means that in general, the board cannot be relied to give correct legal moves after unmake_move
-    // Or rather, the board is reliable again after a forward-move (make_move) has been made
-    // However, moves have been encoded in a way that allows previously (correctly) generated en passant captures to be played
+    // Passes the turn to the opponent, for null move pruning.
+    // The halfmove clock is reset, so that no repetition is detected across the null move.
+    pub fn make_null_move(&mut self) {
+        if self.active_player == Color::Black {
+            self.fullmove_number = self.fullmove_number.saturating_add(1);
+        }
+        self.en_passant_target = None;
+        self.halfmove_clock = 0;
+        self.active_player = self.active_player.opponent();
+    }
+
+    // Whether color has a piece other than pawns and the king, without one zugzwang is common
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        let pieces = self.pieces(PieceType::Knight)
+            | self.pieces(PieceType::Bishop)
+            | self.pieces(PieceType::Rook)
+            | self.pieces(PieceType::Queen);
+        !(pieces & self.color_bbs[color as usize]).is_empty()
+    }
+
+    // The en passant target is restored from the move, as is the halfmove clock unless the move was castling.
+    // The move counters saturate at the u16 limit, where they can be off by one after unmaking a move.
     pub fn unmake_move(&mut self, move_: &LegalMove) {
         self.active_player = self.active_player.opponent();
         let active_player = self.active_player;
-        self.en_passant_target = None;
+        if active_player == Color::Black {
+            self.fullmove_number = self.fullmove_number.saturating_sub(1);
+        }
         match move_ {
             LegalMove::Normal {
                 src,
                 dest,
                 castle_mask,
                 captured_piece,
+                halfmove_clock,
+                en_passant_target,
             } => {
+                self.halfmove_clock = *halfmove_clock;
+                self.en_passant_target = *en_passant_target;
                 let my_piece = self.get_piece_at(*dest);
                 self.set_square(
                     *dest,
@@ -542,8 +683,12 @@ impl Board {
                 dest,
                 castle_mask,
                 captured_piece,
+                halfmove_clock,
+                en_passant_target,
                 ..
             } => {
+                self.halfmove_clock = *halfmove_clock;
+                self.en_passant_target = *en_passant_target;
                 self.set_piece_at(*src, Piece(PieceType::Pawn, active_player));
                 self.set_square(
                     *dest,
@@ -551,7 +696,12 @@ impl Board {
                 );
                 self.castling_rights ^= castle_mask;
             }
-            LegalMove::CastleKingside { castle_mask } => {
+            LegalMove::CastleKingside {
+                castle_mask,
+                en_passant_target,
+            } => {
+                self.halfmove_clock = self.halfmove_clock.saturating_sub(1); // castling never resets the clock
+                self.en_passant_target = *en_passant_target;
                 self.move_piece(
                     Square(File::G, active_player.home_rank()),
                     active_player.king_home_square(),
@@ -562,7 +712,12 @@ impl Board {
                 );
                 self.castling_rights ^= castle_mask;
             }
-            LegalMove::CastleQueenside { castle_mask } => {
+            LegalMove::CastleQueenside {
+                castle_mask,
+                en_passant_target,
+            } => {
+                self.halfmove_clock = self.halfmove_clock.saturating_sub(1);
+                self.en_passant_target = *en_passant_target;
                 self.move_piece(
                     Square(File::C, active_player.home_rank()),
                     active_player.king_home_square(),
@@ -573,14 +728,26 @@ impl Board {
                 );
                 self.castling_rights ^= castle_mask;
             }
-            LegalMove::DoublePawnPush { file } => {
+            LegalMove::DoublePawnPush {
+                file,
+                halfmove_clock,
+                en_passant_target,
+            } => {
+                self.halfmove_clock = *halfmove_clock;
+                self.en_passant_target = *en_passant_target;
                 self.set_piece_at(
                     Square(*file, active_player.pawn_start_rank()),
                     Piece(PieceType::Pawn, self.active_player),
                 );
                 self.clear_square(Square(*file, active_player.double_push_rank()));
             }
-            LegalMove::EnPassantCapture { src, dest } => {
+            LegalMove::EnPassantCapture {
+                src,
+                dest,
+                halfmove_clock,
+            } => {
+                self.halfmove_clock = *halfmove_clock;
+                self.en_passant_target = Some(*dest);
                 self.set_piece_at(*src, self.get_piece_at(*dest).unwrap());
                 self.clear_square(*dest);
                 // put back the captured pawn
@@ -313,6 +313,19 @@ impl Move {
 }
 
 impl LegalMove {
+    pub fn captured_piece(&self) -> Option<PieceType> {
+        match self {
+            LegalMove::Normal { captured_piece, .. }
+            | LegalMove::Promotion { captured_piece, .. } => *captured_piece,
+            LegalMove::EnPassantCapture { .. } => Some(PieceType::Pawn),
+            _ => None,
+        }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.captured_piece().is_some()
+    }
+
     pub fn to_move(&self, board: &Board) -> Move {
         match self {
             LegalMove::Normal { src, dest, .. } => Move::Normal {
@@ -331,7 +344,7 @@ impl LegalMove {
                 dest: *dest,
                 promotion: promotion.to_promotion(),
             },
-            LegalMove::EnPassantCapture { src, dest } => Move::Normal {
+            LegalMove::EnPassantCapture { src, dest, .. } => Move::Normal {
                 src: *src,
                 dest: *dest,
             },
@@ -72,7 +72,8 @@ pub enum Move {
 /* This move is legal in the context of a specific board. Care must be taken to not apply a LegalMove to the wrong board.
 This way, we can skip the legality checks when applying the move.
 The LegalMove is designed in a way to allow all moves to be reversed, and to require minimal computation to apply.
-TODO: model captures for 50 move rule
+Moves that may reset the halfmove clock store the clock from before the move so unmake_move can restore it,
+just like the en passant target from before the move (an en passant capture has it as dest).
 
 Users should NEVER construct a Legalmove directly. Instead, use the Board's get_legal_moves function to get a list of legal moves.
 */
@@ -83,15 +84,21 @@ pub enum LegalMove {
         dest: Square,
         castle_mask: u8,
         captured_piece: Option<PieceType>,
+        halfmove_clock: u16,
+        en_passant_target: Option<Square>,
     },
     DoublePawnPush {
         file: File,
+        halfmove_clock: u16,
+        en_passant_target: Option<Square>,
     },
     CastleKingside {
         castle_mask: u8,
+        en_passant_target: Option<Square>,
     },
     CastleQueenside {
         castle_mask: u8,
+        en_passant_target: Option<Square>,
     },
     Promotion {
         src: Square,
@@ -99,16 +106,27 @@ pub enum LegalMove {
         castle_mask: u8, // in case the pawn captures a rook
         promotion: PieceType,
         captured_piece: Option<PieceType>,
+        halfmove_clock: u16,
+        en_passant_target: Option<Square>,
     },
     EnPassantCapture {
         src: Square,  //dest is given by boards en passant square
         dest: Square, // en passant target
+        halfmove_clock: u16,
     },
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DrawReason {
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum GameState {
     InProgress,
     Mated(Color),
     Stalemate,
+    Draw(DrawReason),
 }
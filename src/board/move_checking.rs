@@ -26,27 +26,10 @@ fn is_sliding_path_free(board: &Board, src: Square, dest: Square) -> bool {
 }
 
 pub fn seek_king(board: &Board, color: Color) -> Square {
-    // search home row first, king is most likely there
-    for rank in [
-        color.home_rank(),
-        Rank::_2,
-        Rank::_7,
-        Rank::_3,
-        Rank::_4,
-        Rank::_5,
-        Rank::_6,
-        color.opp_home_rank(),
-    ] {
-        for file in 0..8 {
-            let pos = Square(File::from_i8(file).unwrap(), rank);
-            if let Some(Piece(PieceType::King, c)) = board.get_piece_at(pos) {
-                if c == color {
-                    return pos;
-                }
-            }
-        }
-    }
-    unreachable!("No king on the board");
+    board
+        .piece_bb(Piece(PieceType::King, color))
+        .lsb()
+        .expect("No king on the board")
 }
 
 // new_board: Move is already carried out, but active player is not switched
@@ -134,10 +117,7 @@ fn get_normal_legal_move_from_pseudolegal(
     src: Square,
     dest: Square,
 ) -> Option<LegalMove> {
-    let src_piece = match board.get_piece_at(src) {
-        Some(piece) => piece,
-        None => return None, // No piece at source
-    };
+    let src_piece = board.get_piece_at(src)?; // No piece at source
 
     if src_piece.1 != board.active_player {
         return None; // tried to move opponent's piece
@@ -145,7 +125,7 @@ fn get_normal_legal_move_from_pseudolegal(
 
     // cannot move to square occupied by my own piece
     let dst_piece = board.get_piece_at(dest);
-    if dst_piece.map_or(false, |p| p.1 == board.active_player) {
+    if dst_piece.is_some_and(|p| p.1 == board.active_player) {
         return None;
     }
     let normal_move = || LegalMove::Normal {
@@ -153,6 +133,8 @@ fn get_normal_legal_move_from_pseudolegal(
         dest,
         castle_mask: get_castling_mask(board, src, dest),
         captured_piece: dst_piece.map(|p| p.0),
+        halfmove_clock: board.halfmove_clock,
+        en_passant_target: board.en_passant_target,
     };
     let legal_move = match src_piece.0 {
         PieceType::Queen | PieceType::Rook | PieceType::Bishop => {
@@ -170,7 +152,11 @@ fn get_normal_legal_move_from_pseudolegal(
                     if board.en_passant_target != Some(dest) {
                         return None; // Invalid move, neither en passant or capture
                     }
-                    LegalMove::EnPassantCapture { src, dest }
+                    LegalMove::EnPassantCapture {
+                        src,
+                        dest,
+                        halfmove_clock: board.halfmove_clock,
+                    }
                 } else {
                     // normal capture
                     normal_move()
@@ -190,13 +176,19 @@ fn get_normal_legal_move_from_pseudolegal(
                     {
                         return None;
                     }
-                    LegalMove::DoublePawnPush { file: src.0 }
+                    LegalMove::DoublePawnPush {
+                        file: src.0,
+                        halfmove_clock: board.halfmove_clock,
+                        en_passant_target: board.en_passant_target,
+                    }
                 } else {
                     LegalMove::Normal {
                         src,
                         dest,
                         castle_mask: 0,
                         captured_piece: None,
+                        halfmove_clock: board.halfmove_clock,
+                        en_passant_target: board.en_passant_target,
                     }
                 }
             }
@@ -238,6 +230,8 @@ fn get_promotion_legal_move_from_pseudolegal(
         castle_mask,
         promotion: promotion.to_piece(),
         captured_piece,
+        halfmove_clock: board.halfmove_clock,
+        en_passant_target: board.en_passant_target,
     })
 }
 
@@ -313,19 +307,21 @@ pub fn get_legal_move_from_move(board: &Board, move_: &Move) -> Option<LegalMove
                 None
             }
         }
-        Move::CastleKingside { .. } => {
+        Move::CastleKingside => {
             if can_castle_kingside(board) {
                 Some(LegalMove::CastleKingside {
                     castle_mask: board.castling_rights & board.active_player.castle_bit_mask(),
+                    en_passant_target: board.en_passant_target,
                 })
             } else {
                 None
             }
         }
-        Move::CastleQueenside { .. } => {
+        Move::CastleQueenside => {
             if can_castle_queenside(board) {
                 Some(LegalMove::CastleQueenside {
                     castle_mask: board.castling_rights & board.active_player.castle_bit_mask(),
+                    en_passant_target: board.en_passant_target,
                 })
             } else {
                 None
@@ -354,19 +350,21 @@ pub fn get_legal_move_from_pseudolegal_move(board: &Board, move_: &Move) -> Opti
                 get_normal_legal_move_from_pseudolegal(board, *src, *dest)
             }
         }
-        Move::CastleKingside { .. } => {
+        Move::CastleKingside => {
             if can_castle_kingside(board) {
                 Some(LegalMove::CastleKingside {
                     castle_mask: board.castling_rights & board.active_player.castle_bit_mask(),
+                    en_passant_target: board.en_passant_target,
                 })
             } else {
                 None
             }
         }
-        Move::CastleQueenside { .. } => {
+        Move::CastleQueenside => {
             if can_castle_queenside(board) {
                 Some(LegalMove::CastleQueenside {
                     castle_mask: board.castling_rights & board.active_player.castle_bit_mask(),
+                    en_passant_target: board.en_passant_target,
                 })
             } else {
                 None
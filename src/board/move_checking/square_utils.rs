@@ -70,14 +70,8 @@ impl Iterator for SlideIter {
 }
 
 pub fn pos_plus(pos: Square, step: (i8, i8)) -> Option<Square> {
-    let new_file = match File::from_i8(pos.0 as i8 + step.0) {
-        Some(file) => file,
-        None => return None,
-    };
-    let new_rank = match Rank::from_i8(pos.1 as i8 + step.1) {
-        Some(rank) => rank,
-        None => return None,
-    };
+    let new_file = File::from_i8(pos.0 as i8 + step.0)?;
+    let new_rank = Rank::from_i8(pos.1 as i8 + step.1)?;
     Some(Square(new_file, new_rank))
 }
 
@@ -88,9 +82,9 @@ fn pos_minus(dest: Square, src: Square) -> (i8, i8) {
     )
 }
 
-const ROOK_DIRS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+pub const ROOK_DIRS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
 
-const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+pub const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
 
 const ALL_DIRS: [(i8, i8); 8] = [
     (0, 1),   // forward
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests;
+
+use super::{
+    models::{LegalMove, Move, PieceType, Square},
+    move_checking::{apply_legal_move, get_legal_move_from_move, is_king_in_check},
+    Board,
+};
+
+fn piece_letter(piece: PieceType) -> Option<char> {
+    match piece {
+        PieceType::Pawn => None,
+        PieceType::Knight => Some('N'),
+        PieceType::Bishop => Some('B'),
+        PieceType::Rook => Some('R'),
+        PieceType::Queen => Some('Q'),
+        PieceType::King => Some('K'),
+    }
+}
+
+fn piece_from_letter(c: char) -> Option<PieceType> {
+    match c {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None,
+    }
+}
+
+// src and dest of all moves except castling
+fn move_squares(move_: &LegalMove, board: &Board) -> Option<(Square, Square)> {
+    match move_.to_move(board) {
+        Move::Normal { src, dest } | Move::Promotion { src, dest, .. } => Some((src, dest)),
+        Move::CastleKingside | Move::CastleQueenside => None,
+    }
+}
+
+fn promotion_piece(move_: &LegalMove) -> Option<PieceType> {
+    match move_ {
+        LegalMove::Promotion { promotion, .. } => Some(*promotion),
+        _ => None,
+    }
+}
+
+fn check_suffix(board: &Board, move_: &LegalMove) -> &'static str {
+    let new_board = apply_legal_move(board, move_);
+    if !is_king_in_check(&new_board) {
+        ""
+    } else if new_board.get_legal_moves().is_empty() {
+        "#"
+    } else {
+        "+"
+    }
+}
+
+impl LegalMove {
+    // Standard algebraic notation, e.g. "Nbd7", "exd6", "O-O-O", "e8=Q+"
+    pub fn to_san(&self, board: &Board) -> String {
+        let mut san = String::new();
+        match move_squares(self, board) {
+            None => san.push_str(match self {
+                LegalMove::CastleKingside { .. } => "O-O",
+                _ => "O-O-O",
+            }),
+            Some((src, dest)) => {
+                let piece = board.get_piece_at(src).unwrap().0;
+                match piece_letter(piece) {
+                    Some(letter) => {
+                        san.push(letter);
+                        // disambiguate between pieces of the same type that can reach dest
+                        let others: Vec<Square> = board
+                            .get_legal_moves()
+                            .iter()
+                            .filter_map(|m| move_squares(m, board))
+                            .filter(|(other_src, other_dest)| {
+                                *other_dest == dest
+                                    && *other_src != src
+                                    && board.get_piece_at(*other_src).unwrap().0 == piece
+                            })
+                            .map(|(other_src, _)| other_src)
+                            .collect();
+                        if !others.is_empty() {
+                            if others.iter().all(|sq| sq.0 != src.0) {
+                                san.push_str(&src.0.to_string());
+                            } else if others.iter().all(|sq| sq.1 != src.1) {
+                                san.push_str(&src.1.to_string());
+                            } else {
+                                san.push_str(&src.to_string());
+                            }
+                        }
+                        if self.is_capture() {
+                            san.push('x');
+                        }
+                    }
+                    None => {
+                        if self.is_capture() {
+                            san.push_str(&src.0.to_string());
+                            san.push('x');
+                        }
+                    }
+                }
+                san.push_str(&dest.to_string());
+                if let Some(promotion) = promotion_piece(self) {
+                    san.push('=');
+                    san.push(piece_letter(promotion).unwrap());
+                }
+            }
+        }
+        san.push_str(check_suffix(board, self));
+        san
+    }
+
+    // Resolves a move in standard algebraic notation against the legal moves of board.
+    // Check and annotation suffixes are ignored, "0-0" and "e8Q" are accepted as well.
+    pub fn from_san(board: &Board, san: &str) -> Result<LegalMove, String> {
+        let trimmed = san.trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = board.get_legal_moves();
+        if trimmed == "O-O" || trimmed == "0-0" {
+            return legal_moves
+                .into_iter()
+                .find(|m| matches!(m, LegalMove::CastleKingside { .. }))
+                .ok_or(format!("Illegal move: {}", san));
+        }
+        if trimmed == "O-O-O" || trimmed == "0-0-0" {
+            return legal_moves
+                .into_iter()
+                .find(|m| matches!(m, LegalMove::CastleQueenside { .. }))
+                .ok_or(format!("Illegal move: {}", san));
+        }
+
+        let mut chars: Vec<char> = trimmed.chars().collect();
+        let piece = match chars.first().and_then(|c| piece_from_letter(*c)) {
+            Some(piece) => {
+                chars.remove(0);
+                piece
+            }
+            None => PieceType::Pawn,
+        };
+        let promotion = match chars.last().and_then(|c| piece_from_letter(*c)) {
+            Some(PieceType::King) => return Err(format!("Invalid promotion piece: {}", san)),
+            Some(promotion) => {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(promotion)
+            }
+            None => None,
+        };
+        if chars.len() < 2 {
+            return Err(format!("Error parsing move: {}", san));
+        }
+        let dest_str: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let dest = Square::from_string(&dest_str)?;
+        // remaining characters: disambiguation and capture marker
+        let mut src_file = None;
+        let mut src_rank = None;
+        for c in chars {
+            match c {
+                'a'..='h' => src_file = Some(c),
+                '1'..='8' => src_rank = Some(c),
+                'x' | ':' => (),
+                _ => return Err(format!("Error parsing move: {}", san)),
+            }
+        }
+
+        let mut candidates = legal_moves.into_iter().filter(|m| {
+            let (src, move_dest) = match move_squares(m, board) {
+                Some(squares) => squares,
+                None => return false,
+            };
+            move_dest == dest
+                && board.get_piece_at(src).unwrap().0 == piece
+                && promotion_piece(m) == promotion
+                && src_file.is_none_or(|f| src.0.to_string().starts_with(f))
+                && src_rank.is_none_or(|r| src.1.to_string().starts_with(r))
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            (None, _) => Err(format!("Illegal move: {}", san)),
+            (Some(_), Some(_)) => Err(format!("Ambiguous move: {}", san)),
+        }
+    }
+}
+
+impl Move {
+    pub fn from_san(board: &Board, san: &str) -> Result<Move, String> {
+        LegalMove::from_san(board, san).map(|m| m.to_move(board))
+    }
+
+    pub fn to_san(&self, board: &Board) -> Result<String, String> {
+        match get_legal_move_from_move(board, self) {
+            Some(legal_move) => Ok(legal_move.to_san(board)),
+            None => Err(format!("Illegal move: {}", self)),
+        }
+    }
+}
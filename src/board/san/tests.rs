@@ -0,0 +1,113 @@
+use rstest::rstest;
+
+use crate::board::{models::LegalMove, test_utils::legal_move, Board};
+
+#[rstest]
+#[case(
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "e2e4",
+    "e4"
+)]
+#[case(
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "g1f3",
+    "Nf3"
+)]
+#[case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1", "O-O")]
+#[case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8", "O-O-O")]
+#[case(
+    "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+    "e5d6",
+    "exd6"
+)]
+#[case(
+    "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+    "e4d5",
+    "exd5"
+)]
+#[case(
+    "rn1qkb1r/ppp1pppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+    "b8d7",
+    "Nbd7"
+)]
+#[case(
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "e2a6",
+    "Bxa6"
+)]
+#[case("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q", "b8=Q+")]
+#[case("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8n", "bxa8=N")]
+#[case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#")]
+#[case("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "a1d1", "Rad1")] // file disambiguation
+#[case("4k3/8/8/8/R7/8/8/R3K3 w - - 0 1", "a1a2", "R1a2")] // rank disambiguation
+#[case("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h4e1", "Qh4e1")] // file and rank disambiguation
+#[case("4k3/8/8/8/8/8/8/1N1NK3 w - - 0 1", "b1c3", "Nbc3")]
+fn test_to_san(#[case] fen: &str, #[case] uci_move: &str, #[case] expected: &str) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(legal_move(&board, uci_move).to_san(&board), expected);
+}
+
+#[rstest]
+#[case(
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "e4",
+    "e2e4"
+)]
+#[case(
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "Nf3",
+    "g1f3"
+)]
+#[case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O", "e1g1")]
+#[case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "O-O-O", "e8c8")]
+#[case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "0-0-0", "e8c8")]
+#[case(
+    "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+    "exd6",
+    "e5d6"
+)]
+#[case(
+    "rn1qkb1r/ppp1pppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1",
+    "Nbd7",
+    "b8d7"
+)]
+#[case("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8=Q+", "b7b8q")]
+#[case("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8N", "b7b8n")]
+#[case("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "Qh4e1", "h4e1")]
+#[case("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "Rad1!?", "a1d1")]
+fn test_from_san(#[case] fen: &str, #[case] san: &str, #[case] expected_uci: &str) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(
+        LegalMove::from_san(&board, san).unwrap(),
+        legal_move(&board, expected_uci)
+    );
+}
+
+#[rstest]
+#[case("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "Rd1")] // ambiguous
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e5")] // illegal
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "O-O")]
+#[case("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8")] // missing promotion piece
+#[case("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8=K")]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "Nz3")]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "")]
+fn test_invalid_san(#[case] fen: &str, #[case] san: &str) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert!(LegalMove::from_san(&board, san).is_err());
+}
+
+#[rstest]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+fn test_san_roundtrip(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    for mv in board.get_legal_moves() {
+        let san = mv.to_san(&board);
+
+        assert_eq!(LegalMove::from_san(&board, &san).unwrap(), mv, "{}", san);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::search::eval::piece_value;
+
+use super::{
+    attacks::{bishop_attacks, rook_attacks},
+    bitboard::Bitboard,
+    board_utils::attackers_to,
+    model_utils::ColorProps,
+    models::{LegalMove, PieceType, Square},
+    Board,
+};
+
+#[cfg(test)]
+mod tests;
+
+const KING_VALUE: i32 = 10000; // only matters as the last capturer, a king can never be recaptured
+
+fn see_value(piece: PieceType) -> i32 {
+    match piece {
+        PieceType::King => KING_VALUE,
+        piece => piece_value(piece),
+    }
+}
+
+const ATTACKER_ORDER: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+impl Board {
+    /*
+    Static exchange evaluation: material won by the side to move if both sides keep recapturing on the
+    destination square of move_, always with their least valuable attacker, and may stop whenever that is better.
+    Sliders behind a capturer join the exchange once it has moved (x-rays). Pins are ignored.
+    Non-captures score whether the moved piece can be won, castling always scores 0.
+    */
+    pub fn see(&self, move_: &LegalMove) -> i32 {
+        let (src, dest, mut occupancy) = match *move_ {
+            LegalMove::Normal { src, dest, .. } | LegalMove::Promotion { src, dest, .. } => {
+                (src, dest, self.occupancy())
+            }
+            LegalMove::EnPassantCapture { src, dest, .. } => {
+                let captured = Square(dest.0, self.active_player.double_push_rank());
+                (
+                    src,
+                    dest,
+                    self.occupancy() ^ Bitboard::from_square(captured),
+                )
+            }
+            LegalMove::DoublePawnPush { file, .. } => (
+                Square(file, self.active_player.pawn_start_rank()),
+                Square(file, self.active_player.double_push_rank()),
+                self.occupancy(),
+            ),
+            LegalMove::CastleKingside { .. } | LegalMove::CastleQueenside { .. } => return 0,
+        };
+        let (mut gain, mut piece_on_dest) = match *move_ {
+            LegalMove::Promotion { promotion, .. } => (
+                see_value(promotion) - see_value(PieceType::Pawn),
+                see_value(promotion),
+            ),
+            _ => (0, see_value(self.get_piece_at(src).unwrap().0)),
+        };
+        gain += move_.captured_piece().map_or(0, see_value);
+
+        // gains[d]: material balance for the side that made capture d, if the exchange stopped after it
+        let mut gains = vec![gain];
+        occupancy ^= Bitboard::from_square(src);
+        let mut attackers = attackers_to(self, dest, occupancy) & occupancy;
+        let mut side = self.active_player.opponent();
+        let diagonal_sliders = self.pieces(PieceType::Bishop) | self.pieces(PieceType::Queen);
+        let straight_sliders = self.pieces(PieceType::Rook) | self.pieces(PieceType::Queen);
+        loop {
+            let side_attackers = attackers & self.color_pieces(side);
+            let Some((piece, square)) = ATTACKER_ORDER.iter().find_map(|&piece| {
+                (side_attackers & self.pieces(piece))
+                    .lsb()
+                    .map(|square| (piece, square))
+            }) else {
+                break;
+            };
+            // the king may not capture into a defended square
+            if piece == PieceType::King
+                && !(attackers & self.color_pieces(side.opponent())).is_empty()
+            {
+                break;
+            }
+            gains.push(piece_on_dest - gains[gains.len() - 1]);
+            piece_on_dest = see_value(piece);
+            occupancy ^= Bitboard::from_square(square);
+            attackers |= bishop_attacks(dest, occupancy) & diagonal_sliders;
+            attackers |= rook_attacks(dest, occupancy) & straight_sliders;
+            attackers &= occupancy;
+            side = side.opponent();
+        }
+        // each side only continues the exchange if that is better than stopping
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap();
+            let previous = gains.last_mut().unwrap();
+            *previous = -(-*previous).max(last);
+        }
+        gains[0]
+    }
+}
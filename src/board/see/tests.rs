@@ -0,0 +1,36 @@
+use rstest::rstest;
+
+use crate::board::{test_utils::legal_move, Board};
+
+#[rstest]
+// undefended pawn
+#[case("4k3/8/8/4p3/8/8/8/4R1K1 w - - 0 1", "e1e5", 100)]
+#[case("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5", 100)]
+// pawn defended by a pawn
+#[case("4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1", "d1d5", -400)]
+// long exchange with x-rays behind the rook and the bishop
+#[case(
+    "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+    "d3e5",
+    -200
+)]
+// the second rook recaptures through the first one
+#[case("4k3/4r3/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5", 100)]
+// a quiet move to a square attacked by a pawn
+#[case("4k3/8/8/8/3p4/8/8/1N2K3 w - - 0 1", "b1c3", -300)]
+#[case("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100)]
+#[case("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8q", 1300)]
+// the new queen is lost to the rook
+#[case("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q", -100)]
+#[case("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1", "e1d2", 100)]
+// after cxd2 the king may not recapture, the bishop now defends d2
+#[case("8/8/8/8/1b1k4/2p5/3n4/3QK3 w - - 0 1", "d1d2", -600)]
+fn test_static_exchange_evaluation(
+    #[case] fen: &str,
+    #[case] uci_move: &str,
+    #[case] expected: i32,
+) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(board.see(&legal_move(&board, uci_move)), expected);
+}
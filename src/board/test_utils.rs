@@ -0,0 +1,10 @@
+use super::{
+    models::{LegalMove, Move},
+    move_checking::get_legal_move_from_move,
+    Board,
+};
+
+// Panics if uci_move is no legal move on board
+pub fn legal_move(board: &Board, uci_move: &str) -> LegalMove {
+    get_legal_move_from_move(board, &Move::from_uci_string(board, uci_move).unwrap()).unwrap()
+}
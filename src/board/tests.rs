@@ -1,13 +1,10 @@
 use rstest::rstest;
-use tests::move_checking::get_legal_move_from_move;
 
 use crate::{
-    board::*,
-    search::perft::{perft},
+    board::{test_utils::legal_move, *},
+    search::perft::perft,
 };
 
-
-
 #[test]
 fn test_fen_default_board() {
     use Color::*;
@@ -284,23 +281,25 @@ fn test_illegal_pawn_move() {
 fn test_queen_promotion() {
     let board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 b kq - 1 1")
         .unwrap();
-    let mv =
-        get_legal_move_from_move(&board, &Move::from_uci_string(&board, "b2a1q").unwrap()).unwrap();
+    let mv = legal_move(&board, "b2a1q");
 
     assert!(board.get_legal_moves().contains(&mv));
 }
 
 #[rstest]
 #[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 2)]
+// every kind of move with an en passant target to restore, including castling and promotions
+#[case(
+    "r3k2r/p1pp1pb1/bn2pnp1/2qPN3/1pP1P3/2N2Q1p/PPpBBPPP/R3K2R b KQkq c3 0 1",
+    2
+)]
 pub fn test_revert_perft(#[case] fen: &str, #[case] depth: i8) {
     let mut board = Board::from_fen(fen).unwrap();
     let initial_fen = board.to_fen();
     for mv in board.get_legal_moves() {
-        let en_passant_target = board.en_passant_target;
         board.make_move(&mv);
         perft_rec(&mut board, depth);
         board.unmake_move(&mv);
-        board.en_passant_target = en_passant_target; // TODO make unmake move do this
         assert_eq!(board.to_fen(), initial_fen);
     }
 }
@@ -312,12 +311,10 @@ fn perft_rec(board: &mut Board, depth: i8) -> u64 {
     let initial_fen = board.to_fen();
     let mut count = 0;
     for mv in board.get_legal_moves() {
-        let en_passant_target = board.en_passant_target;
         board.make_move(&mv);
         let new_positions = perft_rec(board, depth - 1);
         count += new_positions;
         board.unmake_move(&mv);
-        board.en_passant_target = en_passant_target;
         assert_eq!(board.to_fen(), initial_fen);
     }
     count
@@ -330,3 +327,277 @@ pub fn test_castling_right_bits() {
             .unwrap();
     perft(&mut board, 3);
 }
+
+fn assert_bitboards_match_squares(board: &Board) {
+    for index in 0..64 {
+        let square = Square::from_index(index);
+        match board.get_piece_at(square) {
+            Some(piece) => {
+                assert!(board.piece_bb(piece).contains(square));
+                assert!(board.occupancy().contains(square));
+            }
+            None => assert!(!board.occupancy().contains(square)),
+        }
+    }
+    let piece_count: u32 = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::King,
+    ]
+    .iter()
+    .map(|p| board.pieces(*p).count())
+    .sum();
+    assert_eq!(piece_count, board.occupancy().count());
+}
+
+fn bitboard_perft_rec(board: &mut Board, depth: i8) {
+    assert_bitboards_match_squares(board);
+    if depth == 0 {
+        return;
+    }
+    for mv in board.get_legal_moves() {
+        board.make_move(&mv);
+        bitboard_perft_rec(board, depth - 1);
+        board.unmake_move(&mv);
+        assert_bitboards_match_squares(board);
+    }
+}
+
+#[rstest]
+#[case(
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    2
+)]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 2)]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3)]
+fn test_bitboards_in_sync_with_squares(#[case] fen: &str, #[case] depth: i8) {
+    let mut board = Board::from_fen(fen).unwrap();
+    bitboard_perft_rec(&mut board, depth);
+}
+
+#[test]
+fn test_bitboard_iteration_order() {
+    let board = Board::default();
+    let white_rooks: Vec<Square> = board
+        .piece_bb(Piece(PieceType::Rook, Color::White))
+        .into_iter()
+        .collect();
+
+    assert_eq!(
+        white_rooks,
+        vec![Square(File::A, Rank::_1), Square(File::H, Rank::_1)]
+    );
+    assert_eq!(board.occupancy().count(), 32);
+    assert_eq!(board.pieces(PieceType::Pawn).count(), 16);
+    assert_eq!(
+        board.piece_bb(Piece(PieceType::King, Color::Black)).lsb(),
+        Some(Square(File::E, Rank::_8))
+    );
+}
+
+fn ray_attacks(square: Square, occupancy: u64, dirs: &[(i8, i8)]) -> Bitboard {
+    let mut attacks = Bitboard::EMPTY;
+    for dir in dirs {
+        for pos in move_checking::square_utils::RayIter::new(square, *dir) {
+            attacks |= Bitboard::from_square(pos);
+            if occupancy & (1 << pos.to_index()) != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+#[test]
+fn test_magic_attacks_match_rays() {
+    use rand::Rng;
+
+    let mut rng = rand::thread_rng();
+    for index in 0..64 {
+        let square = Square::from_index(index);
+        for _ in 0..200 {
+            let occupancy: u64 = rng.gen::<u64>() & rng.gen::<u64>();
+            assert_eq!(
+                attacks::rook_attacks(square, Bitboard(occupancy)),
+                ray_attacks(square, occupancy, &move_checking::square_utils::ROOK_DIRS)
+            );
+            assert_eq!(
+                attacks::bishop_attacks(square, Bitboard(occupancy)),
+                ray_attacks(square, occupancy, &move_checking::square_utils::BISHOP_DIRS)
+            );
+        }
+    }
+}
+
+#[rstest]
+#[case("a1", 2)]
+#[case("b1", 3)]
+#[case("e4", 8)]
+#[case("h7", 3)]
+fn test_knight_attack_counts(#[case] square: &str, #[case] expected: u32) {
+    let square = Square::from_string(square).unwrap();
+
+    assert_eq!(attacks::knight_attacks(square).count(), expected);
+}
+
+#[test]
+fn test_pawn_attacks() {
+    let e4 = Square(File::E, Rank::_4);
+
+    assert_eq!(
+        attacks::pawn_attacks(Color::White, e4),
+        Bitboard::from_square(Square(File::D, Rank::_5))
+            | Bitboard::from_square(Square(File::F, Rank::_5))
+    );
+    assert_eq!(
+        attacks::pawn_attacks(Color::Black, Square(File::A, Rank::_7)),
+        Bitboard::from_square(Square(File::B, Rank::_6))
+    );
+}
+
+#[rstest]
+#[case("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8")]
+#[case("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10")]
+#[case("8/8/4k3/8/8/3K4/8/8 b - - 97 153")]
+fn test_fen_roundtrip(#[case] fen: &str) {
+    assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+}
+
+#[test]
+fn test_invalid_move_counters() {
+    assert!(Board::from_fen("8/8/4k3/8/8/3K4/8/8 b - - x 1").is_err());
+    assert!(Board::from_fen("8/8/4k3/8/8/3K4/8/8 b - - 0 -1").is_err());
+}
+
+#[rstest]
+#[case("b1c3", 3, 1)] // quiet move increments the clock
+#[case("e2e3", 0, 1)] // pawn move resets the clock
+#[case("e2e4", 0, 1)] // double pawn push resets the clock
+#[case("f3b7", 0, 1)] // capture resets the clock
+#[case("e1g1", 3, 1)] // castling increments the clock
+fn test_move_counters_white(
+    #[case] uci_move: &str,
+    #[case] halfmove_clock: u16,
+    #[case] fullmove_number: u16,
+) {
+    let mut board =
+        Board::from_fen("rnbqkbnr/ppppp1pp/8/8/8/5Q2/PPPPPPPP/RNB1K2R w KQkq - 2 1").unwrap();
+    let mv = legal_move(&board, uci_move);
+
+    board.make_move(&mv);
+    assert_eq!(board.halfmove_clock, halfmove_clock);
+    assert_eq!(board.fullmove_number, fullmove_number);
+
+    board.unmake_move(&mv);
+    assert_eq!(board.halfmove_clock, 2);
+    assert_eq!(board.fullmove_number, 1);
+}
+
+#[test]
+fn test_fullmove_number_increments_after_black_move() {
+    let mut board =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    let mv = legal_move(&board, "g8f6");
+
+    board.make_move(&mv);
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+    );
+
+    board.unmake_move(&mv);
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+}
+
+#[rstest]
+#[case("r3k3/8/8/8/8/8/8/4K3 b q - 65535 65535", "e8c8", 65535, 65535)] // saturated counters
+#[case("r3k3/8/8/8/8/8/8/4K3 b q - 0 0", "e8c8", 1, 1)] // fullmove number 0 is accepted
+#[case("r3k3/8/8/8/8/8/8/4K3 b q - 65535 1", "a8a1", 65535, 2)]
+fn test_move_counters_at_limits(
+    #[case] fen: &str,
+    #[case] uci_move: &str,
+    #[case] halfmove_clock: u16,
+    #[case] fullmove_number: u16,
+) {
+    let mut board = Board::from_fen(fen).unwrap();
+    let mv = legal_move(&board, uci_move);
+
+    board.make_move(&mv);
+    assert_eq!(board.halfmove_clock, halfmove_clock);
+    assert_eq!(board.fullmove_number, fullmove_number);
+
+    board.unmake_move(&mv);
+    board.make_null_move();
+}
+
+#[rstest]
+#[case("8/8/4k3/8/8/3K4/8/8 w - - 0 1", true)] // K vs K
+#[case("8/8/4k3/8/8/3K4/5B2/8 w - - 0 1", true)] // KB vs K
+#[case("8/8/4k3/8/8/3K4/5N2/8 w - - 0 1", true)] // KN vs K
+#[case("8/5b2/4k3/8/8/3K4/6B1/8 w - - 0 1", true)] // bishops on same colored squares
+#[case("8/6b1/4k3/8/8/3K4/6B1/8 w - - 0 1", false)] // bishops on opposite colored squares
+#[case("8/5n2/4k3/8/8/3K4/6N1/8 w - - 0 1", false)] // KN vs KN, mate is possible
+#[case("8/8/4k3/8/8/3K4/5P2/8 w - - 0 1", false)]
+#[case("8/8/4k3/8/8/3K4/5R2/8 w - - 0 1", false)]
+fn test_insufficient_material(#[case] fen: &str, #[case] expected: bool) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(board.is_insufficient_material(), expected);
+    assert_eq!(
+        board.get_gamestate() == GameState::Draw(DrawReason::InsufficientMaterial),
+        expected
+    );
+}
+
+#[test]
+fn test_null_move_passes_the_turn() {
+    let mut board =
+        Board::from_fen("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 4 2").unwrap();
+    board.make_null_move();
+
+    assert_eq!(board.active_player, Color::White);
+    assert_eq!(board.en_passant_target, None);
+    assert_eq!(board.halfmove_clock, 0);
+    assert_eq!(board.fullmove_number, 3);
+}
+
+#[rstest]
+#[case("8/8/4k3/8/8/3K4/5P2/8 w - - 0 1", false, false)]
+#[case("8/8/4k3/8/8/3K4/5N2/8 w - - 0 1", true, false)]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", true, true)]
+fn test_non_pawn_material(#[case] fen: &str, #[case] white: bool, #[case] black: bool) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(board.has_non_pawn_material(Color::White), white);
+    assert_eq!(board.has_non_pawn_material(Color::Black), black);
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 w kq - 0 1")]
+#[case("rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")]
+fn test_capture_moves_are_legal_captures_and_queen_promotions(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let mut expected: Vec<LegalMove> = board
+        .get_legal_moves()
+        .into_iter()
+        .filter(|mv| match mv {
+            LegalMove::Promotion { promotion, .. } => *promotion == PieceType::Queen,
+            _ => mv.is_capture(),
+        })
+        .collect();
+    let mut captures = board.get_capture_moves();
+    let key = |mv: &LegalMove| mv.to_move(&board).to_uci_string(&board);
+    expected.sort_by_key(key);
+    captures.sort_by_key(key);
+
+    assert_eq!(captures, expected);
+}
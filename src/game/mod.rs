@@ -0,0 +1,29 @@
+use crate::board::{models::LegalMove, Board};
+
+mod position_history;
+mod record;
+#[cfg(test)]
+mod tests;
+
+// Zobrist hashes of all positions of a game, the last entry is the current position
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionHistory {
+    hashes: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayedMove {
+    pub move_: LegalMove,
+    pub hash: u64, // hash of the position after the move
+}
+
+// A game from a starting position, with undo/redo.
+// moves holds all played moves including undone ones, the first `ply` of them lead to the current board.
+#[derive(Debug, Clone)]
+pub struct Game {
+    start_position: Board,
+    board: Board,
+    history: PositionHistory,
+    moves: Vec<PlayedMove>,
+    ply: usize,
+}
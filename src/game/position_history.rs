@@ -0,0 +1,72 @@
+use crate::{
+    board::{
+        models::{DrawReason, GameState},
+        Board,
+    },
+    hashing::get_zobrist_hash,
+};
+
+use super::PositionHistory;
+
+impl PositionHistory {
+    pub fn new(initial_hash: u64) -> PositionHistory {
+        PositionHistory {
+            hashes: vec![initial_hash],
+        }
+    }
+
+    pub fn from_board(board: &Board) -> PositionHistory {
+        Self::new(get_zobrist_hash(board))
+    }
+
+    pub fn push(&mut self, hash: u64) {
+        self.hashes.push(hash);
+    }
+
+    pub fn pop(&mut self) -> Option<u64> {
+        // never remove the initial position
+        if self.hashes.len() > 1 {
+            self.hashes.pop()
+        } else {
+            None
+        }
+    }
+
+    pub fn current(&self) -> u64 {
+        *self.hashes.last().unwrap()
+    }
+
+    // How often the current position occurred before.
+    // Positions before the last capture or pawn move (halfmove_clock plies ago) cannot repeat,
+    // and only every second position has the same side to move.
+    pub fn repetition_count(&self, halfmove_clock: u16) -> usize {
+        let current = self.current();
+        self.hashes
+            .iter()
+            .rev()
+            .take(halfmove_clock as usize + 1)
+            .skip(2)
+            .step_by(2)
+            .filter(|hash| **hash == current)
+            .count()
+    }
+
+    // Cheaper check for the search: any earlier occurrence is scored as a draw
+    pub fn is_repetition(&self, halfmove_clock: u16) -> bool {
+        self.repetition_count(halfmove_clock) > 0
+    }
+
+    pub fn is_threefold_repetition(&self, halfmove_clock: u16) -> bool {
+        self.repetition_count(halfmove_clock) >= 2
+    }
+
+    // Board state including draws by repetition, board must be the position of the last pushed hash
+    pub fn get_gamestate(&self, board: &Board) -> GameState {
+        match board.get_gamestate() {
+            GameState::InProgress if self.is_threefold_repetition(board.halfmove_clock) => {
+                GameState::Draw(DrawReason::ThreefoldRepetition)
+            }
+            gamestate => gamestate,
+        }
+    }
+}
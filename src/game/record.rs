@@ -0,0 +1,142 @@
+use crate::{
+    board::{
+        models::{GameState, LegalMove, Move},
+        move_checking::get_legal_move_from_move,
+        Board,
+    },
+    hashing::update_zobrist_hash,
+};
+
+use super::{Game, PlayedMove, PositionHistory};
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::default())
+    }
+}
+
+impl Game {
+    pub fn new(start_position: Board) -> Game {
+        Game {
+            start_position,
+            board: start_position,
+            history: PositionHistory::from_board(&start_position),
+            moves: Vec::new(),
+            ply: 0,
+        }
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Game, String> {
+        Ok(Self::new(Board::from_fen(fen)?))
+    }
+
+    pub fn start_position(&self) -> &Board {
+        &self.start_position
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn history(&self) -> &PositionHistory {
+        &self.history
+    }
+
+    // moves leading from the start position to the current board
+    pub fn moves(&self) -> &[PlayedMove] {
+        &self.moves[..self.ply]
+    }
+
+    pub fn get_gamestate(&self) -> GameState {
+        self.history.get_gamestate(&self.board)
+    }
+
+    // move_ must be legal on the current board, i.e. obtained from board().get_legal_moves()
+    // Discards all moves that could be redone
+    pub fn play_legal_move(&mut self, move_: &LegalMove) {
+        self.moves.truncate(self.ply);
+        let played = PlayedMove {
+            move_: move_.clone(),
+            hash: update_zobrist_hash(&self.board, self.history.current(), move_),
+        };
+        self.apply(&played);
+        self.moves.push(played);
+    }
+
+    pub fn play_move(&mut self, move_: &Move) -> Result<(), String> {
+        match get_legal_move_from_move(&self.board, move_) {
+            Some(legal_move) => {
+                self.play_legal_move(&legal_move);
+                Ok(())
+            }
+            None => Err(format!("Illegal move: {}", move_)),
+        }
+    }
+
+    pub fn play_uci_move(&mut self, move_str: &str) -> Result<(), String> {
+        let move_ = Move::from_uci_string(&self.board, move_str)?;
+        self.play_move(&move_)
+    }
+
+    pub fn play_san_move(&mut self, san: &str) -> Result<(), String> {
+        let move_ = LegalMove::from_san(&self.board, san)?;
+        self.play_legal_move(&move_);
+        Ok(())
+    }
+
+    fn apply(&mut self, played: &PlayedMove) {
+        self.board.make_move(&played.move_);
+        self.history.push(played.hash);
+        self.ply += 1;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.ply > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.ply < self.moves.len()
+    }
+
+    // Takes back the last move, it can be replayed with redo
+    pub fn undo(&mut self) -> Option<&LegalMove> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.ply -= 1;
+        let played = &self.moves[self.ply];
+        self.board.unmake_move(&played.move_);
+        self.history.pop();
+        Some(&played.move_)
+    }
+
+    pub fn redo(&mut self) -> Option<&LegalMove> {
+        if !self.can_redo() {
+            return None;
+        }
+        let played = self.moves[self.ply].clone();
+        self.apply(&played);
+        Some(&self.moves[self.ply - 1].move_)
+    }
+
+    // Calls f with each played move and the board it was played on
+    pub fn replay(&self, mut f: impl FnMut(&Board, &LegalMove)) {
+        let mut board = self.start_position;
+        for played in self.moves() {
+            f(&board, &played.move_);
+            board.make_move(&played.move_);
+        }
+    }
+
+    pub fn to_uci_move_list(&self) -> Vec<String> {
+        let mut move_list = Vec::with_capacity(self.ply);
+        self.replay(|board, move_| move_list.push(move_.to_move(board).to_uci_string(board)));
+        move_list
+    }
+
+    pub fn to_san_move_list(&self) -> Vec<String> {
+        let mut move_list = Vec::with_capacity(self.ply);
+        self.replay(|board, move_| move_list.push(move_.to_san(board)));
+        move_list
+    }
+}
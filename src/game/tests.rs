@@ -0,0 +1,160 @@
+use crate::board::{
+    models::{DrawReason, GameState},
+    Board,
+};
+
+use super::{Game, PositionHistory};
+
+fn play_moves(board: &Board, moves: &[&str]) -> (Board, PositionHistory) {
+    let mut game = Game::new(*board);
+    for uci_move in moves {
+        game.play_uci_move(uci_move).unwrap();
+    }
+    (*game.board(), game.history().clone())
+}
+
+#[test]
+fn test_threefold_repetition() {
+    let knight_shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    let (board, history) = play_moves(&Board::default(), &knight_shuffle);
+
+    assert_eq!(history.repetition_count(board.halfmove_clock), 1);
+    assert!(history.is_repetition(board.halfmove_clock));
+    assert_eq!(history.get_gamestate(&board), GameState::InProgress);
+
+    let (board, history) = play_moves(
+        &Board::default(),
+        &[knight_shuffle, knight_shuffle].concat(),
+    );
+
+    assert_eq!(history.repetition_count(board.halfmove_clock), 2);
+    assert_eq!(
+        history.get_gamestate(&board),
+        GameState::Draw(DrawReason::ThreefoldRepetition)
+    );
+}
+
+#[test]
+fn test_repetition_needs_same_side_to_move() {
+    // the rook returns to the same square, but with the other side to move
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let (board, history) = play_moves(&board, &["a1a2", "e8d8", "a2a1"]);
+
+    assert!(!history.is_repetition(board.halfmove_clock));
+}
+
+#[test]
+fn test_no_repetition_across_irreversible_move() {
+    let (board, history) = play_moves(
+        &Board::default(),
+        &["g1f3", "g8f6", "f3g1", "f6g8", "e2e3", "e7e6"],
+    );
+
+    assert_eq!(board.halfmove_clock, 0);
+    assert!(!history.is_repetition(board.halfmove_clock));
+}
+
+#[test]
+fn test_fifty_move_rule() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80").unwrap();
+    let (board, history) = play_moves(&board, &["a1a2"]);
+
+    assert_eq!(
+        history.get_gamestate(&board),
+        GameState::Draw(DrawReason::FiftyMoveRule)
+    );
+}
+
+#[test]
+fn test_mate_takes_precedence_over_fifty_move_rule() {
+    let board = Board::from_fen("k7/8/1K6/8/8/8/8/7R w - - 99 80").unwrap();
+    let (board, history) = play_moves(&board, &["h1h8"]);
+
+    assert_eq!(
+        history.get_gamestate(&board),
+        GameState::Mated(board.active_player)
+    );
+}
+
+#[test]
+fn test_undo_redo() {
+    let mut game = Game::default();
+    for uci_move in ["e2e4", "d7d5", "e4d5", "c7c5"] {
+        game.play_uci_move(uci_move).unwrap();
+    }
+    let fen_after_c5 = game.board().to_fen();
+
+    game.undo();
+    game.undo();
+    assert_eq!(
+        game.board().to_fen(),
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2"
+    );
+    assert_eq!(game.history().current(), game.moves()[1].hash);
+
+    game.redo();
+    game.redo();
+    assert!(!game.can_redo());
+    assert_eq!(game.board().to_fen(), fen_after_c5);
+
+    // en passant must still be possible after undo/redo
+    game.play_uci_move("d5c6").unwrap();
+    assert_eq!(game.moves().len(), 5);
+}
+
+#[test]
+fn test_undo_to_start() {
+    let mut game = Game::default();
+    game.play_uci_move("g1f3").unwrap();
+
+    assert!(game.undo().is_some());
+    assert!(game.undo().is_none());
+    assert_eq!(game.board(), game.start_position());
+    assert_eq!(
+        game.history(),
+        &PositionHistory::from_board(&Board::default())
+    );
+}
+
+#[test]
+fn test_new_move_discards_redo() {
+    let mut game = Game::default();
+    game.play_uci_move("e2e4").unwrap();
+    game.play_uci_move("e7e5").unwrap();
+    game.undo();
+    game.play_uci_move("c7c5").unwrap();
+
+    assert!(!game.can_redo());
+    assert_eq!(game.to_uci_move_list(), vec!["e2e4", "c7c5"]);
+}
+
+#[test]
+fn test_illegal_move_is_rejected() {
+    let mut game = Game::default();
+
+    assert!(game.play_uci_move("e2e5").is_err());
+    assert!(!game.can_undo());
+}
+
+#[test]
+fn test_uci_move_list_with_castling() {
+    let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    game.play_uci_move("e1g1").unwrap();
+    game.play_uci_move("e8c8").unwrap();
+
+    assert_eq!(game.to_uci_move_list(), vec!["e1g1", "e8c8"]);
+}
+
+#[test]
+fn test_san_move_list() {
+    let mut game = Game::default();
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        game.play_san_move(san).unwrap();
+    }
+
+    assert_eq!(game.to_san_move_list(), vec!["f3", "e5", "g4", "Qh4#"]);
+    assert_eq!(
+        game.to_uci_move_list(),
+        vec!["f2f3", "e7e5", "g2g4", "d8h4"]
+    );
+}
@@ -1,28 +1,66 @@
+use std::sync::atomic::AtomicU64;
 
-
-use crate::board::{
-    model_utils::ColorProps,
-    models::{Color, File, LegalMove, Piece, PieceType, Square},
-    move_checking::square_utils::SquareIter,
-    Board,
+use crate::{
+    board::{
+        model_utils::ColorProps,
+        models::{Color, File, LegalMove, Piece, PieceType, Square},
+        Board,
+    },
+    search::score::Score,
 };
 
+mod packed_move;
 #[cfg(test)]
 mod tests;
 mod transposition_table;
 mod zobrist_keys;
 
+// How the stored value relates to the true value of the position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower, // fail high: the true value is at least value
+    Upper, // fail low: the true value is at most value
+}
+
+// A move in 16 bits: source square, destination square and a flag for promotions and castling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackedMove(u16);
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TranspEntry {
     pub depth: u8,
-    pub value: f32,
+    pub value: Score, // mate scores are relative to this position, see Score::to_tt
+    pub bound: Bound,
+    pub best_move: Option<PackedMove>, // the move that raised alpha or caused the cutoff
+    pub age: u8,                       // generation of the search that stored the entry, set by put
+}
+
+const BUCKET_SIZE: usize = 4;
+const DEPTH_PREFERRED_SLOTS: usize = BUCKET_SIZE - 1; // the last slot of a bucket is always replaced
+
+/*
+An entry packed into data, see TranspEntry::pack, and key = hash ^ data.
+Both words are written without a lock, so a reader can see the key of one write and the data of another.
+Such torn slots fail the check key ^ data == hash and are treated as empty.
+*/
+#[derive(Debug, Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+// One cache line of entries for hashes with the same index
+#[derive(Debug, Default)]
+#[repr(align(64))]
+struct Bucket {
+    slots: [Slot; BUCKET_SIZE],
 }
 
+// Can be shared between search threads, only resizing, clearing and starting a new search need exclusive access
 pub struct TranspTable {
-    // TODO add eviction policy
-    table: Vec<Option<(u64, TranspEntry)>>,
-    size: usize,
-    occupancy: usize,
+    buckets: Vec<Bucket>,
+    generation: u8,
 }
 
 fn get_piece_square_key(piece: Piece, square: Square) -> u64 {
@@ -32,7 +70,7 @@ fn get_piece_square_key(piece: Piece, square: Square) -> u64 {
 
 pub fn get_zobrist_hash(board: &Board) -> u64 {
     let mut hash = 0;
-    for square in SquareIter::new() {
+    for square in board.occupancy() {
         if let Some(piece) = board.get_piece_at(square) {
             hash ^= get_piece_square_key(piece, square);
         }
@@ -58,6 +96,7 @@ pub fn update_zobrist_hash(board: &Board, mut board_hash: u64, move_: &LegalMove
             dest,
             castle_mask,
             captured_piece,
+            ..
         } => {
             let src_piece = board.get_piece_at(*src).unwrap();
             board_hash ^= get_piece_square_key(src_piece, *dest);
@@ -72,7 +111,7 @@ pub fn update_zobrist_hash(board: &Board, mut board_hash: u64, move_: &LegalMove
             board_hash ^=
                 zobrist_keys::CASTLING_KEYS[(board.castling_rights ^ castle_mask) as usize];
         }
-        LegalMove::DoublePawnPush { file } => {
+        LegalMove::DoublePawnPush { file, .. } => {
             board_hash ^= get_piece_square_key(
                 Piece(PieceType::Pawn, board.active_player),
                 Square(*file, board.active_player.pawn_start_rank()),
@@ -83,7 +122,7 @@ pub fn update_zobrist_hash(board: &Board, mut board_hash: u64, move_: &LegalMove
             );
             board_hash ^= zobrist_keys::EN_PASSANT_KEYS[*file as usize];
         }
-        LegalMove::CastleKingside { castle_mask } => {
+        LegalMove::CastleKingside { castle_mask, .. } => {
             board_hash ^= get_piece_square_key(
                 Piece(PieceType::King, board.active_player),
                 Square(File::E, board.active_player.home_rank()),
@@ -104,7 +143,7 @@ pub fn update_zobrist_hash(board: &Board, mut board_hash: u64, move_: &LegalMove
             board_hash ^=
                 zobrist_keys::CASTLING_KEYS[(board.castling_rights ^ castle_mask) as usize];
         }
-        LegalMove::CastleQueenside { castle_mask } => {
+        LegalMove::CastleQueenside { castle_mask, .. } => {
             board_hash ^= get_piece_square_key(
                 Piece(PieceType::King, board.active_player),
                 Square(File::E, board.active_player.home_rank()),
@@ -131,6 +170,7 @@ pub fn update_zobrist_hash(board: &Board, mut board_hash: u64, move_: &LegalMove
             castle_mask,
             promotion,
             captured_piece,
+            ..
         } => {
             board_hash ^= get_piece_square_key(Piece(PieceType::Pawn, board.active_player), *src);
             board_hash ^= get_piece_square_key(Piece(*promotion, board.active_player), *dest);
@@ -144,15 +184,75 @@ pub fn update_zobrist_hash(board: &Board, mut board_hash: u64, move_: &LegalMove
             board_hash ^=
                 zobrist_keys::CASTLING_KEYS[(board.castling_rights ^ castle_mask) as usize];
         }
-        LegalMove::EnPassantCapture { src, dest } => {
+        LegalMove::EnPassantCapture { src, dest, .. } => {
             board_hash ^= get_piece_square_key(Piece(PieceType::Pawn, board.active_player), *src);
             board_hash ^= get_piece_square_key(Piece(PieceType::Pawn, board.active_player), *dest);
             board_hash ^= get_piece_square_key(
                 Piece(PieceType::Pawn, board.active_player.opponent()),
-                Square(dest.0, board.active_player.double_push_rank()),
+                Square(dest.0, board.active_player.opponent().double_push_rank()),
             );
         }
     }
     board_hash ^= zobrist_keys::BLACK_TO_MOVE_KEY;
     board_hash
 }
+
+// Precondition: the null move has not yet been applied to board, see Board::make_null_move
+pub fn update_zobrist_hash_null(board: &Board, mut board_hash: u64) -> u64 {
+    if let Some(Square(target_file, _)) = board.en_passant_target {
+        board_hash ^= zobrist_keys::EN_PASSANT_KEYS[target_file as usize];
+    }
+    board_hash ^ zobrist_keys::BLACK_TO_MOVE_KEY
+}
+
+// Zobrist key of the pawns alone, for caching pawn structure evaluations
+pub fn get_pawn_hash(board: &Board) -> u64 {
+    let mut hash = 0;
+    for square in board.pieces(PieceType::Pawn) {
+        if let Some(piece) = board.get_piece_at(square) {
+            hash ^= get_piece_square_key(piece, square);
+        }
+    }
+    hash
+}
+
+// Precondition: move has not yet been applied to board
+pub fn update_pawn_hash(board: &Board, mut pawn_hash: u64, move_: &LegalMove) -> u64 {
+    let pawn = Piece(PieceType::Pawn, board.active_player);
+    let opponent_pawn = Piece(PieceType::Pawn, board.active_player.opponent());
+    match move_ {
+        LegalMove::Normal {
+            src,
+            dest,
+            captured_piece,
+            ..
+        } => {
+            if board.get_piece_at(*src) == Some(pawn) {
+                pawn_hash ^= get_piece_square_key(pawn, *src);
+                pawn_hash ^= get_piece_square_key(pawn, *dest);
+            }
+            if *captured_piece == Some(PieceType::Pawn) {
+                pawn_hash ^= get_piece_square_key(opponent_pawn, *dest);
+            }
+        }
+        LegalMove::DoublePawnPush { file, .. } => {
+            pawn_hash ^=
+                get_piece_square_key(pawn, Square(*file, board.active_player.pawn_start_rank()));
+            pawn_hash ^=
+                get_piece_square_key(pawn, Square(*file, board.active_player.double_push_rank()));
+        }
+        LegalMove::Promotion { src, .. } => {
+            pawn_hash ^= get_piece_square_key(pawn, *src); // a pawn can only capture pieces on the last rank
+        }
+        LegalMove::EnPassantCapture { src, dest, .. } => {
+            pawn_hash ^= get_piece_square_key(pawn, *src);
+            pawn_hash ^= get_piece_square_key(pawn, *dest);
+            pawn_hash ^= get_piece_square_key(
+                opponent_pawn,
+                Square(dest.0, board.active_player.opponent().double_push_rank()),
+            );
+        }
+        LegalMove::CastleKingside { .. } | LegalMove::CastleQueenside { .. } => (),
+    }
+    pawn_hash
+}
@@ -0,0 +1,66 @@
+use crate::board::{
+    models::{LegalMove, Move, PromotionPieceType, Square},
+    Board,
+};
+
+use super::PackedMove;
+
+const SQUARE_MASK: u16 = 0x3f;
+const FLAG_SHIFT: u16 = 12;
+// flags, 0 is a normal move
+const PROMOTION_FLAGS: [(PromotionPieceType, u16); 4] = [
+    (PromotionPieceType::Knight, 1),
+    (PromotionPieceType::Bishop, 2),
+    (PromotionPieceType::Rook, 3),
+    (PromotionPieceType::Queen, 4),
+];
+const CASTLE_KINGSIDE_FLAG: u16 = 5;
+const CASTLE_QUEENSIDE_FLAG: u16 = 6;
+
+fn pack_squares(src: Square, dest: Square, flag: u16) -> PackedMove {
+    PackedMove(src.to_index() as u16 | (dest.to_index() as u16) << 6 | flag << FLAG_SHIFT)
+}
+
+impl PackedMove {
+    pub fn new(move_: &Move) -> PackedMove {
+        match move_ {
+            Move::Normal { src, dest } => pack_squares(*src, *dest, 0),
+            Move::Promotion {
+                src,
+                dest,
+                promotion,
+            } => {
+                let (_, flag) = PROMOTION_FLAGS
+                    .iter()
+                    .find(|(piece, _)| piece == promotion)
+                    .unwrap();
+                pack_squares(*src, *dest, *flag)
+            }
+            Move::CastleKingside => PackedMove(CASTLE_KINGSIDE_FLAG << FLAG_SHIFT),
+            Move::CastleQueenside => PackedMove(CASTLE_QUEENSIDE_FLAG << FLAG_SHIFT),
+        }
+    }
+
+    pub fn from_legal_move(move_: &LegalMove, board: &Board) -> PackedMove {
+        PackedMove::new(&move_.to_move(board))
+    }
+
+    pub fn to_move(self) -> Move {
+        let src = Square::from_index((self.0 & SQUARE_MASK) as usize);
+        let dest = Square::from_index((self.0 >> 6 & SQUARE_MASK) as usize);
+        match self.0 >> FLAG_SHIFT {
+            0 => Move::Normal { src, dest },
+            CASTLE_KINGSIDE_FLAG => Move::CastleKingside,
+            CASTLE_QUEENSIDE_FLAG => Move::CastleQueenside,
+            flag => Move::Promotion {
+                src,
+                dest,
+                promotion: PROMOTION_FLAGS
+                    .iter()
+                    .find(|(_, f)| *f == flag)
+                    .map(|(piece, _)| *piece)
+                    .unwrap(),
+            },
+        }
+    }
+}
@@ -1,10 +1,19 @@
-use crate::board::{
-    models::{LegalMove, Square},
-    move_checking::apply_legal_move,
-    Board,
+use rstest::rstest;
+
+use crate::{
+    board::{
+        models::{LegalMove, Move, Square},
+        move_checking::apply_legal_move,
+        Board,
+    },
+    search::score::Score,
 };
 
-use super::{get_zobrist_hash, update_zobrist_hash};
+use super::{
+    get_pawn_hash, get_zobrist_hash, update_pawn_hash, update_zobrist_hash,
+    update_zobrist_hash_null, Bound, Bucket, PackedMove, TranspEntry, TranspTable, BUCKET_SIZE,
+    DEPTH_PREFERRED_SLOTS,
+};
 
 #[test]
 pub fn test_transposition_hashes_match() {
@@ -16,24 +25,32 @@ pub fn test_transposition_hashes_match() {
             dest: Square::from_string("e3").unwrap(),
             castle_mask: 0,
             captured_piece: None,
+            halfmove_clock: 0,
+            en_passant_target: None,
         },
         LegalMove::Normal {
             src: Square::from_string("b1").unwrap(),
             dest: Square::from_string("c3").unwrap(),
             castle_mask: 0,
             captured_piece: None,
+            halfmove_clock: 0,
+            en_passant_target: None,
         },
         LegalMove::Normal {
             src: Square::from_string("e7").unwrap(),
             dest: Square::from_string("e6").unwrap(),
             castle_mask: 0,
             captured_piece: None,
+            halfmove_clock: 0,
+            en_passant_target: None,
         },
         LegalMove::Normal {
             src: Square::from_string("b8").unwrap(),
             dest: Square::from_string("c6").unwrap(),
             castle_mask: 0,
             captured_piece: None,
+            halfmove_clock: 0,
+            en_passant_target: None,
         },
     );
     let (board_1, hash_1) = [
@@ -62,3 +79,247 @@ pub fn test_transposition_hashes_match() {
 }
 
 // TODO larger testcase found through real search
+
+#[test]
+fn test_transposition_table_size_and_clear() {
+    let entry = TranspEntry::new(3, Score(150), Bound::Exact, None);
+    let mut table = TranspTable::new(1);
+
+    assert_eq!(std::mem::size_of::<Bucket>(), 64);
+    assert_eq!(table.size(), 1024 * 1024 / 64 * BUCKET_SIZE);
+
+    table.put(42, entry);
+    assert_eq!(table.get(42), Some(entry));
+
+    table.clear();
+    assert_eq!(table.get(42), None);
+    assert_eq!(table.hashfull(), 0);
+
+    table.put(42, entry);
+    table.resize_mb(2);
+    assert_eq!(table.size(), 2 * 1024 * 1024 / 64 * BUCKET_SIZE);
+    assert_eq!(table.get(42), None);
+}
+
+#[test]
+fn test_transposition_table_stamps_generation() {
+    let mut table = TranspTable::new(1);
+    table.put(42, TranspEntry::new(3, Score(150), Bound::Lower, None));
+    assert_eq!(table.get(42).unwrap().age, table.generation());
+
+    table.new_search();
+    table.put(43, TranspEntry::new(3, Score(150), Bound::Upper, None));
+    assert_eq!(table.get(43).unwrap().age, table.generation());
+    assert_ne!(table.get(42).unwrap().age, table.generation());
+}
+
+// hashes that only differ in the upper 32 bits share a bucket
+fn same_bucket(key: u64) -> u64 {
+    7 | key << 32
+}
+
+#[test]
+fn test_transposition_table_keeps_deep_entries() {
+    let table = TranspTable::new(1);
+    for depth in 1..=DEPTH_PREFERRED_SLOTS as u64 {
+        table.put(
+            same_bucket(depth),
+            TranspEntry::new(10 + depth as u8, Score(0), Bound::Exact, None),
+        );
+    }
+    // shallower than all depth-preferred entries, only the always-replace slot is overwritten
+    for key in 100..110 {
+        table.put(
+            same_bucket(key),
+            TranspEntry::new(1, Score(0), Bound::Exact, None),
+        );
+    }
+
+    for depth in 1..=DEPTH_PREFERRED_SLOTS as u64 {
+        assert!(table.get(same_bucket(depth)).is_some());
+    }
+    assert!(table.get(same_bucket(108)).is_none());
+    assert!(table.get(same_bucket(109)).is_some());
+
+    // a deeper entry replaces the shallowest depth-preferred one
+    table.put(
+        same_bucket(200),
+        TranspEntry::new(20, Score(0), Bound::Exact, None),
+    );
+    assert!(table.get(same_bucket(1)).is_none());
+    assert!(table.get(same_bucket(200)).is_some());
+    assert!(table.get(same_bucket(109)).is_some());
+}
+
+#[test]
+fn test_transposition_table_replaces_old_entries() {
+    let mut table = TranspTable::new(1);
+    for key in 1..=DEPTH_PREFERRED_SLOTS as u64 {
+        table.put(
+            same_bucket(key),
+            TranspEntry::new(10, Score(0), Bound::Exact, None),
+        );
+    }
+    for _ in 0..10 {
+        table.new_search();
+    }
+    table.put(
+        same_bucket(100),
+        TranspEntry::new(2, Score(0), Bound::Exact, None),
+    );
+
+    assert!(table.get(same_bucket(100)).is_some());
+    assert_eq!(
+        (1..=DEPTH_PREFERRED_SLOTS as u64)
+            .filter(|&key| table.get(same_bucket(key)).is_some())
+            .count(),
+        DEPTH_PREFERRED_SLOTS - 1
+    );
+}
+
+#[test]
+fn test_transposition_table_keeps_best_move_of_same_position() {
+    let table = TranspTable::new(1);
+    let best_move = PackedMove::new(&Move::CastleKingside);
+    table.put(
+        42,
+        TranspEntry::new(5, Score(30), Bound::Lower, Some(best_move)),
+    );
+    table.put(42, TranspEntry::new(6, Score(10), Bound::Upper, None));
+
+    let entry = table.get(42).unwrap();
+    assert_eq!(entry.depth, 6);
+    assert_eq!(entry.best_move, Some(best_move));
+}
+
+#[test]
+fn test_hashfull_counts_current_search() {
+    let mut table = TranspTable::new(1);
+    for hash in 0..1000 {
+        table.put(hash, TranspEntry::new(1, Score(0), Bound::Exact, None));
+    }
+    assert_eq!(table.hashfull(), 250); // one entry in each of the first 1000 / BUCKET_SIZE buckets
+
+    table.new_search();
+    assert_eq!(table.hashfull(), 0);
+}
+
+#[rstest]
+#[case(TranspEntry::new(0, Score(0), Bound::Exact, None))]
+#[case(TranspEntry::new(7, Score(-1234), Bound::Lower, Some(PackedMove::new(&Move::CastleQueenside))))]
+#[case(TranspEntry::new(255, Score::mated_in(3), Bound::Upper, None))]
+#[case(TranspEntry::new(12, Score::mate_in(5), Bound::Exact, Some(PackedMove::new(&Move::Normal { src: Square::from_string("e2").unwrap(), dest: Square::from_string("e4").unwrap() }))))]
+#[case(TranspEntry::new(1, -Score::INFINITY, Bound::Lower, None))]
+fn test_transposition_entry_survives_packing(#[case] entry: TranspEntry) {
+    let mut table = TranspTable::new(1);
+    for _ in 0..5 {
+        table.new_search();
+    }
+    table.put(42, entry);
+
+    let stored = table.get(42).unwrap();
+    assert_eq!(stored, TranspEntry { age: 5, ..entry });
+}
+
+#[test]
+fn test_transposition_table_shared_between_threads() {
+    // every thread writes entries whose value is derived from the hash, readers must never see a mix of two entries
+    let table = TranspTable::new(1);
+    let value_of = |hash: u64| Score((hash % 20000) as i32 - 10000);
+    std::thread::scope(|scope| {
+        for thread_id in 0..4u64 {
+            let table = &table;
+            scope.spawn(move || {
+                for i in 0..20000u64 {
+                    let hash = (i % 500).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ thread_id << 60;
+                    table.put(
+                        hash,
+                        TranspEntry::new((i % 30) as u8, value_of(hash), Bound::Exact, None),
+                    );
+                    let other = (i * 7 % 500).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ thread_id << 60;
+                    if let Some(entry) = table.get(other) {
+                        assert_eq!(entry.value, value_of(other));
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1")]
+#[case("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1")]
+#[case("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N w - - 0 1")]
+fn test_packed_move_round_trip(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let moves = board.get_legal_moves();
+    let packed: Vec<PackedMove> = moves
+        .iter()
+        .map(|m| PackedMove::from_legal_move(m, &board))
+        .collect();
+    for (move_, packed_move) in moves.iter().zip(&packed) {
+        assert_eq!(packed_move.to_move(), move_.to_move(&board));
+    }
+    // distinct moves must stay distinct
+    for (i, packed_move) in packed.iter().enumerate() {
+        assert!(!packed[i + 1..].contains(packed_move));
+    }
+}
+
+#[rstest]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")]
+#[case("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2")]
+fn test_null_move_hash_matches(#[case] fen: &str) {
+    let mut board = Board::from_fen(fen).unwrap();
+    let hash = update_zobrist_hash_null(&board, get_zobrist_hash(&board));
+    board.make_null_move();
+
+    assert_eq!(hash, get_zobrist_hash(&board));
+}
+
+#[rstest]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 b kq - 0 1")]
+#[case("rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")]
+#[case("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1")]
+fn test_pawn_hash_updates_match(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let pawn_hash = get_pawn_hash(&board);
+    for move_ in board.get_legal_moves() {
+        let new_board = apply_legal_move(&board, &move_);
+
+        assert_eq!(
+            update_pawn_hash(&board, pawn_hash, &move_),
+            get_pawn_hash(&new_board)
+        );
+    }
+}
+
+#[test]
+fn test_pawn_hash_ignores_pieces() {
+    let board = Board::from_fen("4k3/pp6/8/8/8/8/PP6/R3K3 w - - 0 1").unwrap();
+    let moved_rook = Board::from_fen("4k3/pp6/8/8/8/8/PP6/3RK3 b - - 0 1").unwrap();
+
+    assert_eq!(get_pawn_hash(&board), get_pawn_hash(&moved_rook));
+    assert_ne!(get_pawn_hash(&board), get_zobrist_hash(&board));
+}
+
+#[rstest]
+#[case("rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")]
+#[case("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 b kq - 0 1")]
+fn test_hash_updates_match(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let hash = get_zobrist_hash(&board);
+    for move_ in board.get_legal_moves() {
+        let new_board = apply_legal_move(&board, &move_);
+
+        assert_eq!(
+            update_zobrist_hash(&board, hash, &move_),
+            get_zobrist_hash(&new_board)
+        );
+    }
+}
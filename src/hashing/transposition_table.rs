@@ -1,34 +1,186 @@
-use super::{TranspEntry, TranspTable};
+use std::sync::atomic::Ordering;
+
+use crate::search::score::Score;
+
+use super::{
+    Bound, Bucket, PackedMove, Slot, TranspEntry, TranspTable, BUCKET_SIZE, DEPTH_PREFERRED_SLOTS,
+};
+
+const BYTES_PER_MB: usize = 1024 * 1024;
+
+impl TranspEntry {
+    // the age is filled in by TranspTable::put
+    pub fn new(
+        depth: u8,
+        value: Score,
+        bound: Bound,
+        best_move: Option<PackedMove>,
+    ) -> TranspEntry {
+        TranspEntry {
+            depth,
+            value,
+            bound,
+            best_move,
+            age: 0,
+        }
+    }
+
+    // bits 0-15 value, 16-31 best move, 32-39 depth, 40-47 age, 48-49 bound. 0 is an empty slot.
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        // a1a1 is never a legal move, so 0 can stand for no move
+        let best_move = self.best_move.map_or(0, |move_| move_.0);
+        // all scores are within -Score::INFINITY..=Score::INFINITY and fit into 16 bits
+        (self.value.0 as i16 as u16 as u64)
+            | (best_move as u64) << 16
+            | (self.depth as u64) << 32
+            | (self.age as u64) << 40
+            | bound << 48
+    }
+
+    fn unpack(data: u64) -> Option<TranspEntry> {
+        let bound = match data >> 48 & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        let best_move = (data >> 16) as u16;
+        Some(TranspEntry {
+            depth: (data >> 32) as u8,
+            value: Score(data as u16 as i16 as i32),
+            bound,
+            best_move: (best_move != 0).then_some(PackedMove(best_move)),
+            age: (data >> 40) as u8,
+        })
+    }
+}
+
+impl Slot {
+    // None for empty and torn slots
+    fn load(&self, hash: u64) -> Option<TranspEntry> {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed);
+        if key ^ data != hash {
+            return None;
+        }
+        TranspEntry::unpack(data)
+    }
+
+    // the stored entry regardless of its hash
+    fn load_any(&self) -> Option<TranspEntry> {
+        TranspEntry::unpack(self.data.load(Ordering::Relaxed))
+    }
+
+    fn store(&self, hash: u64, entry: &TranspEntry) {
+        let data = entry.pack();
+        self.data.store(data, Ordering::Relaxed);
+        self.key.store(hash ^ data, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.data.store(0, Ordering::Relaxed);
+        self.key.store(0, Ordering::Relaxed);
+    }
+}
 
 impl TranspTable {
-    pub fn new(size: usize) -> TranspTable {
-        let table = vec![None; size];
+    // Largest table that fits into size_mb megabytes
+    pub fn new(size_mb: usize) -> TranspTable {
+        let bucket_count = (size_mb * BYTES_PER_MB / std::mem::size_of::<Bucket>()).max(1);
         TranspTable {
-            table,
-            size,
-            occupancy: 0,
+            buckets: (0..bucket_count).map(|_| Bucket::default()).collect(),
+            generation: 0,
         }
     }
 
-    pub fn get(&self, hash: u64) -> Option<&TranspEntry> {
-        let index = hash as usize % self.size;
-        if let Some((stored_hash, value)) = &self.table[index] {
-            if *stored_hash == hash {
-                return Some(value);
-            }
+    // Discards all entries
+    pub fn resize_mb(&mut self, size_mb: usize) {
+        *self = TranspTable::new(size_mb);
+    }
+
+    pub fn clear(&mut self) {
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.slots) {
+            slot.reset();
         }
-        None
     }
 
-    pub fn put(&mut self, hash: u64, value: TranspEntry) {
-        let index = hash as usize % self.size;
-        if self.table[index].is_none() {
-            self.occupancy += 1;
+    // Called at the start of every search, entries of older searches become less valuable
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn generation(&self) -> u8 {
+        self.generation
+    }
+
+    // number of entries
+    pub fn size(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    fn bucket(&self, hash: u64) -> &Bucket {
+        &self.buckets[(hash % self.buckets.len() as u64) as usize]
+    }
+
+    pub fn get(&self, hash: u64) -> Option<TranspEntry> {
+        self.bucket(hash)
+            .slots
+            .iter()
+            .find_map(|slot| slot.load(hash))
+    }
+
+    /*
+    An entry of the same position is always overwritten.
+    Otherwise the least valuable depth-preferred slot is replaced if the new entry searched at least as deep,
+    entries of older searches count as shallower. If the new entry is shallower, it goes to the always-replace slot,
+    so recent positions can still be found.
+    Concurrent writes to the same bucket may lose one of the entries, which only costs a bit of search work.
+    */
+    pub fn put(&self, hash: u64, mut value: TranspEntry) {
+        let generation = self.generation;
+        value.age = generation;
+        let slots = &self.bucket(hash).slots;
+        if let Some((slot, old)) = slots
+            .iter()
+            .find_map(|slot| slot.load(hash).map(|old| (slot, old)))
+        {
+            if value.best_move.is_none() {
+                value.best_move = old.best_move;
+            }
+            slot.store(hash, &value);
+            return;
+        }
+        let priority = |slot: &Slot| match slot.load_any() {
+            None => i32::MIN,
+            Some(entry) => entry.depth as i32 - 2 * generation.wrapping_sub(entry.age) as i32,
+        };
+        let victim = slots[..DEPTH_PREFERRED_SLOTS]
+            .iter()
+            .min_by_key(|slot| priority(slot))
+            .unwrap();
+        if priority(victim) <= value.depth as i32 {
+            victim.store(hash, &value);
+        } else {
+            slots[BUCKET_SIZE - 1].store(hash, &value);
         }
-        self.table[index] = Some((hash, value)); // TODO add eviction policy
     }
 
-    pub fn get_occupancy_factor(&self) -> f32 {
-        self.occupancy as f32 / self.size as f32
+    // Permille of the first 1000 entries that were written in the current search, as reported in "info hashfull"
+    pub fn hashfull(&self) -> usize {
+        let sample_buckets = 1000 / BUCKET_SIZE;
+        let used = self
+            .buckets
+            .iter()
+            .take(sample_buckets)
+            .flat_map(|bucket| &bucket.slots)
+            .filter_map(|slot| slot.load_any())
+            .filter(|entry| entry.age == self.generation)
+            .count();
+        used * 1000 / (sample_buckets.min(self.buckets.len()) * BUCKET_SIZE)
     }
 }
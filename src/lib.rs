@@ -1,5 +1,7 @@
 pub mod board;
+pub mod game;
 pub mod hashing;
+pub mod pgn;
 pub mod players;
 pub mod search;
 pub mod uci;
@@ -2,27 +2,38 @@ use otus::{
     board::{
         model_utils::ColorProps,
         models::{Color, GameState},
-        move_checking::apply_legal_move,
         Board,
     },
+    game::{Game, PositionHistory},
     hashing::TranspTable,
+    pgn::PgnGame,
     players::{ChessPlayer, HumanPlayer, RandomPlayer},
     search::{
-        eval::smart_eval,
-        minimax::{search_minimax_threaded_cached},
+        eval::{smart_eval, trace_with_tables, PieceSquareTables},
+        minimax::search_minimax_threaded_cached,
+        search_params::SearchParams,
+        time_management::SearchLimits,
     },
-    uci::UciEngine,
+    uci::{options::DEFAULT_HASH_MB, UciEngine},
 };
 
 fn perftest() {
     let board = Board::default();
     let (_tx, rx) = std::sync::mpsc::channel();
-    let mut transp_table = TranspTable::new(2 << 24);
-    search_minimax_threaded_cached(&board, 6, smart_eval, &mut transp_table, rx);
-    println!(
-        "Transposition table occupancy: {}",
-        transp_table.get_occupancy_factor()
+    let mut transp_table = TranspTable::new(DEFAULT_HASH_MB);
+    let history = PositionHistory::from_board(&board);
+    search_minimax_threaded_cached(
+        &board,
+        &SearchLimits::from_depth(6),
+        1,
+        1,
+        SearchParams::default(),
+        &mut smart_eval,
+        &mut transp_table,
+        &history,
+        rx,
     );
+    println!("Transposition table hashfull: {}", transp_table.hashfull());
 }
 
 fn main() {
@@ -30,11 +41,30 @@ fn main() {
     if args.len() > 1 {
         match args[1].as_str() {
             "debug" => {
-                run_test_game();
+                // optionally saves the game to the PGN file given as next argument
+                run_test_game(args.get(2).map(|s| s.as_str()));
             }
             "perftest" => {
                 perftest();
             }
+            "eval" => {
+                // eval [--pst <file>] [fen], the starting position without a FEN
+                let (tables, fen_args) = match args.get(2).map(|s| s.as_str()) {
+                    Some("--pst") => match args.get(3) {
+                        Some(path) => (PieceSquareTables::load(path), &args[4..]),
+                        None => (Err("Missing file after --pst".to_string()), &args[3..]),
+                    },
+                    _ => (Ok(PieceSquareTables::default()), &args[2..]),
+                };
+                let board = match fen_args.join(" ").as_str() {
+                    "" => Ok(Board::default()),
+                    fen => Board::from_fen(fen).map_err(|e| format!("Invalid FEN: {}", e)),
+                };
+                match tables.and_then(|tables| Ok((tables, board?))) {
+                    Ok((tables, board)) => println!("{}", trace_with_tables(&board, &tables)),
+                    Err(e) => println!("{}", e),
+                }
+            }
             _ => println!("Invalid argument"),
         }
     } else {
@@ -42,27 +72,38 @@ fn main() {
     }
 }
 
-fn run_test_game() {
+fn run_test_game(pgn_path: Option<&str>) {
     let human_player = HumanPlayer;
     let random_player = RandomPlayer;
-    match run_game(&human_player, &random_player) {
+    match run_game(&human_player, &random_player, pgn_path).get_gamestate() {
         GameState::Mated(color) => println!("{} wins!", color.opponent()),
         GameState::Stalemate => println!("Stalemate!"),
+        GameState::Draw(reason) => println!("Draw by {:?}!", reason),
         GameState::InProgress => unreachable!("Game should have ended"),
     }
 }
 
-pub fn run_game(white_player: &dyn ChessPlayer, black_player: &dyn ChessPlayer) -> GameState {
-    let mut board = Board::default();
-    loop {
+// Plays a game from the starting position until it is over, the finished game is saved as PGN if a path is given
+pub fn run_game(
+    white_player: &dyn ChessPlayer,
+    black_player: &dyn ChessPlayer,
+    pgn_path: Option<&str>,
+) -> Game {
+    let mut game = Game::default();
+    while game.get_gamestate() == GameState::InProgress {
+        let board = game.board();
         let m = match board.active_player {
-            Color::White => white_player.propose_move(&board),
-            Color::Black => black_player.propose_move(&board),
+            Color::White => white_player.propose_move(board),
+            Color::Black => black_player.propose_move(board),
         };
-        board = apply_legal_move(&board, &m);
-        match board.get_gamestate() {
-            GameState::InProgress => (),
-            gs => return gs,
+        game.play_legal_move(&m);
+    }
+    if let Some(path) = pgn_path {
+        let mut pgn_game = PgnGame::new(game.clone());
+        pgn_game.set_tag("Event", "otus test game");
+        if let Err(e) = std::fs::write(path, pgn_game.to_pgn()) {
+            println!("Could not save game to {}: {}", path, e);
         }
     }
+    game
 }
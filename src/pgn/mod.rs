@@ -0,0 +1,109 @@
+use std::fmt;
+
+use crate::{
+    board::{
+        models::{Color, GameState},
+        Board,
+    },
+    game::Game,
+};
+
+mod reader;
+#[cfg(test)]
+mod tests;
+mod writer;
+
+// Result of a game as written in PGN: "1-0", "0-1", "1/2-1/2" or "*"
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Unknown, // game still in progress or result not known
+}
+
+// A game read from or written to PGN.
+// Variations and NAGs are skipped when reading, comments are kept.
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>, // in file order, the Seven Tag Roster comes first when writing
+    pub game: Game,
+    pub comments: Vec<(usize, String)>, // (ply after which the comment appears, text), 0 = before the first move
+    pub result: GameResult,
+}
+
+impl GameResult {
+    pub fn from_gamestate(gamestate: GameState) -> GameResult {
+        match gamestate {
+            GameState::Mated(Color::White) => GameResult::BlackWins,
+            GameState::Mated(Color::Black) => GameResult::WhiteWins,
+            GameState::Stalemate | GameState::Draw(_) => GameResult::Draw,
+            GameState::InProgress => GameResult::Unknown,
+        }
+    }
+
+    pub fn from_pgn_string(s: &str) -> Option<GameResult> {
+        match s {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+impl PgnGame {
+    // Wraps a game with placeholder values for the Seven Tag Roster, the result is taken from the game state
+    pub fn new(game: Game) -> PgnGame {
+        let result = GameResult::from_gamestate(game.get_gamestate());
+        let mut pgn_game = PgnGame {
+            tags: Vec::new(),
+            game,
+            comments: Vec::new(),
+            result,
+        };
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Date" => "????.??.??".to_string(),
+                "Result" => result.to_string(),
+                _ => "?".to_string(),
+            };
+            pgn_game.set_tag(name, &value);
+        }
+        if *pgn_game.game.start_position() != Board::default() {
+            let fen = pgn_game.game.start_position().to_fen();
+            pgn_game.set_tag("SetUp", "1");
+            pgn_game.set_tag("FEN", &fen);
+        }
+        pgn_game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+}
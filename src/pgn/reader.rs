@@ -0,0 +1,210 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::game::Game;
+
+use super::{GameResult, PgnGame};
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    San(String),
+    Result(GameResult),
+}
+
+// Splits PGN text into tags, comments, moves and results. Variations, NAGs and move numbers are dropped.
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(pgn: &'a str) -> Lexer<'a> {
+        Lexer {
+            chars: pgn.chars().peekable(),
+        }
+    }
+
+    fn read_until(&mut self, end: char) -> String {
+        let mut s = String::new();
+        for c in self.chars.by_ref() {
+            if c == end {
+                break;
+            }
+            s.push(c);
+        }
+        s
+    }
+
+    fn read_tag(&mut self) -> Result<Token, String> {
+        let name: String = self.read_until('"').trim().to_string();
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') => value.extend(self.chars.next()),
+                Some('"') => break,
+                Some(c) => value.push(c),
+                None => return Err(format!("Unterminated tag: {}", name)),
+            }
+        }
+        self.read_until(']');
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("Invalid tag name: {}", name));
+        }
+        Ok(Token::Tag(name, value))
+    }
+
+    // Skips a (possibly nested) variation, the opening parenthesis is already consumed
+    fn skip_variation(&mut self) {
+        let mut depth = 1;
+        while let Some(c) = self.chars.next() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                '{' => {
+                    self.read_until('}');
+                }
+                ';' => {
+                    self.read_until('\n');
+                }
+                _ => (),
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
+    fn read_symbol(&mut self, first: char) -> String {
+        let mut symbol = first.to_string();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || "{}()[];$".contains(c) {
+                break;
+            }
+            symbol.push(c);
+            self.chars.next();
+        }
+        symbol
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let c = self.chars.next()?;
+            match c {
+                '[' => return Some(self.read_tag()),
+                '{' => return Some(Ok(Token::Comment(self.read_until('}').trim().to_string()))),
+                ';' => return Some(Ok(Token::Comment(self.read_until('\n').trim().to_string()))),
+                '(' => self.skip_variation(),
+                '$' => while self.chars.next_if(char::is_ascii_digit).is_some() {},
+                '%' => {
+                    self.read_until('\n');
+                }
+                c if c.is_whitespace() => (),
+                c => {
+                    let symbol = self.read_symbol(c);
+                    if let Some(result) = GameResult::from_pgn_string(&symbol) {
+                        return Some(Ok(Token::Result(result)));
+                    }
+                    // strip move numbers like "12." or "12...", they may be attached to the move
+                    let san = match symbol.rfind('.') {
+                        Some(i) => &symbol[i + 1..],
+                        None => symbol.as_str(),
+                    };
+                    if !san.is_empty() && !san.chars().all(|c| c.is_ascii_digit()) {
+                        return Some(Ok(Token::San(san.to_string())));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Collects the tokens of one game
+#[derive(Default)]
+struct GameBuilder {
+    tags: Vec<(String, String)>,
+    moves: Vec<String>,
+    comments: Vec<(usize, String)>,
+    result: Option<GameResult>,
+}
+
+impl GameBuilder {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.moves.is_empty()
+    }
+
+    fn build(self) -> Result<PgnGame, String> {
+        let tag = |name: &str| {
+            self.tags
+                .iter()
+                .find(|(tag_name, _)| tag_name == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let mut game = match tag("FEN") {
+            Some(fen) => Game::from_fen(fen)?,
+            None => Game::default(),
+        };
+        for san in &self.moves {
+            game.play_san_move(san)?;
+        }
+        let result = self
+            .result
+            .or_else(|| tag("Result").and_then(GameResult::from_pgn_string))
+            .unwrap_or(GameResult::Unknown);
+        Ok(PgnGame {
+            tags: self.tags,
+            game,
+            comments: self.comments,
+            result,
+        })
+    }
+}
+
+impl PgnGame {
+    // Reads all games of a PGN file
+    pub fn parse_all(pgn: &str) -> Result<Vec<PgnGame>, String> {
+        let mut games = Vec::new();
+        let mut builder = GameBuilder::default();
+        let mut finish = |builder: GameBuilder| -> Result<(), String> {
+            let game_number = games.len() + 1;
+            games.push(
+                builder
+                    .build()
+                    .map_err(|e| format!("Game {}: {}", game_number, e))?,
+            );
+            Ok(())
+        };
+        for token in Lexer::new(pgn) {
+            match token? {
+                Token::Tag(name, value) => {
+                    // tags after movetext start the next game, even if the result was missing
+                    if !builder.moves.is_empty() {
+                        finish(std::mem::take(&mut builder))?;
+                    }
+                    builder.tags.push((name, value));
+                }
+                Token::Comment(comment) => builder.comments.push((builder.moves.len(), comment)),
+                Token::San(san) => builder.moves.push(san),
+                Token::Result(result) => {
+                    builder.result = Some(result);
+                    finish(std::mem::take(&mut builder))?;
+                }
+            }
+        }
+        if !builder.is_empty() {
+            finish(builder)?;
+        }
+        Ok(games)
+    }
+
+    // Reads the first game of a PGN file
+    pub fn parse(pgn: &str) -> Result<PgnGame, String> {
+        PgnGame::parse_all(pgn)?
+            .into_iter()
+            .next()
+            .ok_or("No game found".to_string())
+    }
+}
@@ -0,0 +1,135 @@
+use rstest::rstest;
+
+use crate::{
+    board::{models::GameState, Board},
+    game::Game,
+};
+
+use super::{GameResult, PgnGame};
+
+const TWO_GAMES: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+
+[Event "Scholar"]
+[White "?"]
+[Black "?"]
+
+1.e4 e5 2.Qh5 $2 (2.Nf3 Nc6 (2...d6) 3.Bb5) Nc6 3.Bc4 Nf6?? ; blunder
+4.Qxf7# 1-0
+"#;
+
+#[test]
+fn test_parse_multiple_games() {
+    let games = PgnGame::parse_all(TWO_GAMES).unwrap();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].tag("White"), Some("Fischer, Robert J."));
+    assert_eq!(games[0].result, GameResult::Draw);
+    assert_eq!(games[0].game.moves().len(), 85);
+    assert_eq!(
+        games[0].comments,
+        vec![(5, "This opening is called the Ruy Lopez.".to_string())]
+    );
+    assert_eq!(games[1].tag("Event"), Some("Scholar"));
+    assert_eq!(games[1].result, GameResult::WhiteWins);
+    assert_eq!(games[1].game.moves().len(), 7);
+    assert_eq!(games[1].comments, vec![(6, "blunder".to_string())]);
+    assert_eq!(
+        games[1].game.get_gamestate(),
+        GameState::Mated(crate::board::models::Color::Black)
+    );
+}
+
+#[test]
+fn test_parse_from_fen() {
+    let pgn = r#"[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/8/R3K3 b - - 0 30"]
+
+30... Kd7 31. Ra7+ *"#;
+    let pgn_game = PgnGame::parse(pgn).unwrap();
+
+    assert_eq!(pgn_game.result, GameResult::Unknown);
+    assert_eq!(pgn_game.game.to_uci_move_list(), vec!["e8d7", "a1a7"]);
+    assert_eq!(pgn_game.game.board().fullmove_number, 31);
+}
+
+#[rstest]
+#[case("1. e4 e5 2. Ke3 *")] // illegal move
+#[case("[Event \"unterminated]")]
+#[case("[FEN \"not a fen\"]\n\n1. e4 *")]
+fn test_parse_errors(#[case] pgn: &str) {
+    assert!(PgnGame::parse_all(pgn).is_err());
+}
+
+#[test]
+fn test_write_seven_tag_roster() {
+    let mut game = Game::default();
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        game.play_san_move(san).unwrap();
+    }
+    let mut pgn_game = PgnGame::new(game);
+    pgn_game.set_tag("White", "Otus \"the owl\"");
+    pgn_game.set_tag("Annotator", "otus");
+    pgn_game.comments.push((2, "too late".to_string()));
+
+    let expected = r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "Otus \"the owl\""]
+[Black "?"]
+[Result "0-1"]
+[Annotator "otus"]
+
+1. f3 e5 {too late} 2. g4 Qh4# 0-1
+"#;
+    assert_eq!(pgn_game.to_pgn(), expected);
+}
+
+#[test]
+fn test_write_from_fen_starts_with_black_move_number() {
+    let fen = "4k3/8/8/8/8/8/8/R3K3 b - - 0 30";
+    let mut game = Game::from_fen(fen).unwrap();
+    game.play_san_move("Kd7").unwrap();
+    let pgn = PgnGame::new(game).to_pgn();
+
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 b - - 0 30\"]\n"));
+    assert!(pgn.ends_with("\n30... Kd7 *\n"));
+}
+
+#[test]
+fn test_roundtrip() {
+    for pgn_game in PgnGame::parse_all(TWO_GAMES).unwrap() {
+        let pgn = pgn_game.to_pgn();
+        let reparsed = PgnGame::parse(&pgn).unwrap();
+
+        for (name, value) in &pgn_game.tags {
+            assert_eq!(reparsed.tag(name), Some(value.as_str()));
+        }
+        assert_eq!(reparsed.comments, pgn_game.comments);
+        assert_eq!(reparsed.result, pgn_game.result);
+        assert_eq!(
+            reparsed.game.to_san_move_list(),
+            pgn_game.game.to_san_move_list()
+        );
+        assert!(pgn.lines().all(|line| line.len() <= 80));
+    }
+    assert_eq!(
+        *PgnGame::new(Game::default()).game.board(),
+        Board::default()
+    );
+}
@@ -0,0 +1,77 @@
+use crate::board::models::Color;
+
+use super::{PgnGame, SEVEN_TAG_ROSTER};
+
+const MAX_LINE_LENGTH: usize = 80;
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Joins movetext tokens with spaces, breaking lines before MAX_LINE_LENGTH
+fn wrap(tokens: &[String]) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            text.push(' ');
+            line_length += 1;
+        }
+        text.push_str(token);
+        line_length += token.len();
+    }
+    text
+}
+
+impl PgnGame {
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        let result = self.result.to_string();
+        // Seven Tag Roster in its prescribed order, then all other tags
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => result.as_str(),
+                "Date" => self.tag(name).unwrap_or("????.??.??"),
+                _ => self.tag(name).unwrap_or("?"),
+            };
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        let mut ply = 0;
+        let push_comments = |tokens: &mut Vec<String>, ply: usize| {
+            for (_, comment) in self.comments.iter().filter(|(p, _)| *p == ply) {
+                tokens.push(format!("{{{}}}", comment.replace('}', ")")));
+            }
+        };
+        push_comments(&mut tokens, 0);
+        let mut needs_move_number = true; // after comments black moves need "n..." as well
+        self.game.replay(|board, move_| {
+            match board.active_player {
+                Color::White => tokens.push(format!("{}.", board.fullmove_number)),
+                Color::Black if needs_move_number => {
+                    tokens.push(format!("{}...", board.fullmove_number))
+                }
+                Color::Black => (),
+            }
+            tokens.push(move_.to_san(board));
+            ply += 1;
+            let comment_count = tokens.len();
+            push_comments(&mut tokens, ply);
+            needs_move_number = tokens.len() != comment_count;
+        });
+        tokens.push(result);
+        pgn.push_str(&wrap(&tokens));
+        pgn.push('\n');
+        pgn
+    }
+}
@@ -15,6 +15,9 @@ impl HumanPlayer {
             "0-0-0" => Move::CastleQueenside,
             s => {
                 let parts: Vec<&str> = s.split_whitespace().collect();
+                if parts.len() == 1 {
+                    return LegalMove::from_san(board, parts[0]);
+                }
                 if parts.len() != 2 {
                     return Err("Error parsing move".to_string());
                 }
@@ -55,7 +58,10 @@ impl HumanPlayer {
 impl ChessPlayer for HumanPlayer {
     fn propose_move(&self, board: &Board) -> LegalMove {
         println!("{}", board);
-        println!("You are {}. Enter your move: ", board.active_player);
+        println!(
+            "You are {}. Enter your move (e.g. \"Nf3\" or \"g1 f3\"): ",
+            board.active_player
+        );
         loop {
             match self.try_get_move_input(board) {
                 Ok(m) => return m,
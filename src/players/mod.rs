@@ -1,6 +1,11 @@
 use crate::{
     board::{models::LegalMove, Board},
+    game::Game,
     hashing::TranspTable,
+    search::{
+        evaluator::SmartEval, nnue::NnueEval, search_params::SearchParams,
+        time_management::SearchLimits,
+    },
 };
 
 pub mod human_player;
@@ -12,7 +17,13 @@ pub trait ChessPlayer {
 
 pub trait UciPlayer {
     // propose_move should print `bestmove` to stdout and react to "stop" command
-    fn propose_move(&mut self, board: &Board, rx: std::sync::mpsc::Receiver<()>);
+    // the move is proposed for the current board of the game, the search stays within limits
+    fn propose_move(
+        &mut self,
+        game: &Game,
+        limits: &SearchLimits,
+        rx: std::sync::mpsc::Receiver<()>,
+    );
 }
 
 pub struct HumanPlayer;
@@ -20,4 +31,9 @@ pub struct RandomPlayer;
 
 pub struct Otus {
     transp_table: TranspTable,
+    threads: usize,
+    multi_pv: usize, // number of best root moves reported in "info"
+    search_params: SearchParams,
+    evaluator: SmartEval,
+    nnue: Option<NnueEval>, // used instead of evaluator if a network is loaded
 }
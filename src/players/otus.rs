@@ -1,12 +1,18 @@
-
+use std::sync::Arc;
 
 use crate::{
-    board::{Board},
+    board::Board,
+    game::Game,
     hashing::TranspTable,
     search::{
-        eval::smart_eval,
-        minimax::{search_minimax_threaded_cached},
+        eval::{trace_with_tables, PieceSquareTables},
+        evaluator::{Evaluator, SmartEval},
+        minimax::search_minimax_threaded_cached,
+        nnue::{Network, NnueEval},
+        search_params::SearchParams,
+        time_management::SearchLimits,
     },
+    uci::options::{EngineOption, DEFAULT_HASH_MB},
 };
 
 use super::{Otus, UciPlayer};
@@ -18,16 +24,113 @@ impl Default for Otus {
 }
 
 impl Otus {
-    // TODO make cache size, depth and other parameters configurable
     pub fn new() -> Self {
         Self {
-            transp_table: TranspTable::new(2 << 24),
+            transp_table: TranspTable::new(DEFAULT_HASH_MB),
+            threads: 1,
+            multi_pv: 1,
+            search_params: SearchParams::default(),
+            evaluator: SmartEval::default(),
+            nnue: None,
+        }
+    }
+
+    pub fn set_option(&mut self, option: EngineOption) {
+        match option {
+            EngineOption::Hash(size_mb) => self.transp_table.resize_mb(size_mb),
+            EngineOption::Threads(threads) => self.threads = threads,
+            EngineOption::MultiPv(multi_pv) => self.multi_pv = multi_pv,
+            EngineOption::ClearHash => self.transp_table.clear(),
+            EngineOption::NullMove(on) => self.search_params.null_move = on,
+            EngineOption::LateMoveReductions(on) => self.search_params.late_move_reductions = on,
+            EngineOption::ReverseFutility(on) => self.search_params.reverse_futility = on,
+            EngineOption::Futility(on) => self.search_params.futility = on,
+            EngineOption::CheckExtensions(on) => self.search_params.check_extensions = on,
+            EngineOption::AspirationWindows(on) => self.search_params.aspiration_windows = on,
+            // scores of another evaluator must not mix with the new ones in the transposition table
+            EngineOption::EvalFile(None) => {
+                if self.nnue.take().is_some() {
+                    self.transp_table.clear();
+                }
+            }
+            EngineOption::EvalFile(Some(path)) => match Network::load(&path) {
+                Ok(network) => {
+                    println!(
+                        "info string Loaded network {} with {} hidden neurons",
+                        path,
+                        network.hidden()
+                    );
+                    self.nnue = Some(NnueEval::new(Arc::new(network)));
+                    self.transp_table.clear();
+                }
+                Err(e) => println!("info string {}", e),
+            },
+            EngineOption::PstFile(None) => {
+                self.evaluator = SmartEval::default();
+                self.transp_table.clear();
+            }
+            EngineOption::PstFile(Some(path)) => match PieceSquareTables::load(&path) {
+                Ok(tables) => {
+                    println!("info string Loaded piece-square tables {}", path);
+                    self.evaluator = SmartEval::new(tables);
+                    self.transp_table.clear();
+                }
+                Err(e) => println!("info string {}", e),
+            },
+        }
+    }
+
+    // every term of the evaluation with the piece-square tables in use
+    // The network has no breakdown into terms, so only its score is printed
+    pub fn print_eval(&self, board: &Board) {
+        match &self.nnue {
+            Some(nnue) => {
+                // after a search the accumulators are those of its root, not of board
+                let mut nnue = nnue.clone();
+                nnue.reset(board);
+                let score = nnue.evaluate(board);
+                println!("nnue score {} cp for {}", score.0, board.active_player);
+                println!("the hand-made evaluation is not used while a network is loaded");
+            }
+            None => println!("{}", trace_with_tables(board, self.evaluator.tables())),
         }
     }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
 }
 
 impl UciPlayer for Otus {
-    fn propose_move(&mut self, board: &Board, rx: std::sync::mpsc::Receiver<()>) {
-        search_minimax_threaded_cached(board, 6, smart_eval, &mut self.transp_table, rx);
+    fn propose_move(
+        &mut self,
+        game: &Game,
+        limits: &SearchLimits,
+        rx: std::sync::mpsc::Receiver<()>,
+    ) {
+        match &mut self.nnue {
+            Some(nnue) => search_minimax_threaded_cached(
+                game.board(),
+                limits,
+                self.multi_pv,
+                self.threads,
+                self.search_params,
+                nnue,
+                &mut self.transp_table,
+                game.history(),
+                rx,
+            ),
+            None => search_minimax_threaded_cached(
+                game.board(),
+                limits,
+                self.multi_pv,
+                self.threads,
+                self.search_params,
+                &mut self.evaluator,
+                &mut self.transp_table,
+                game.history(),
+                rx,
+            ),
+        }
     }
 }
@@ -1,156 +1,315 @@
-use std::cmp::min;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
 use crate::board::{
+    bitboard::Bitboard,
     model_utils::ColorProps,
-    models::{Color, File, Piece, PieceType, Square},
-    move_checking::{
-        is_king_in_check, seek_king,
-        square_utils::{pos_plus, SquareIter},
-    },
+    models::{Color, Piece, PieceType, Square},
+    move_checking::{is_king_in_check, seek_king, square_utils::pos_plus},
     Board,
 };
 
-pub fn get_material_eval(board: &Board) -> f32 {
-    let mut material_balance = 0.0;
-    for square in SquareIter::new() {
-        if let Some(Piece(piece, owner)) = board.get_piece_at(square) {
-            let value = match piece {
-                PieceType::Pawn => 1.0,
-                PieceType::Knight => 3.0,
-                PieceType::Bishop => 3.0,
-                PieceType::Rook => 5.0,
-                PieceType::Queen => 9.0,
-                PieceType::King => continue,
-            };
-            material_balance += if owner == board.active_player {
-                value
-            } else {
-                -value
-            };
-        }
+use super::score::Score;
+
+mod pawns;
+mod pieces;
+mod pst;
+#[cfg(test)]
+mod tests;
+mod trace;
+
+// contribution of each piece type to the game phase, the king does not count
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24; // all pieces of the starting position
+const CHECK_PENALTY: TaperedScore = TaperedScore { mg: -30, eg: -30 };
+const KING_EDGE: i32 = 10; // per square the enemy king is closer to the edge than the center
+
+/*
+Middlegame and endgame value of every piece on every square, including its material value.
+Indexed by PieceType and square as seen by white (a1 = 0), black pieces use the mirrored square.
+The material values alone are kept by PieceType, to tell them apart from the square bonuses.
+The default tables are data, see eval/pst.txt for the format, and can be overridden from a file.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceSquareTables {
+    pub mg: [[i32; 64]; 6],
+    pub eg: [[i32; 64]; 6],
+    pub material: [TaperedScore; 6],
+}
+
+// Pawn structure terms of a position, they only depend on the pawns and are cached in a PawnTable
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PawnEntry {
+    pub key: u64,                 // see hashing::get_pawn_hash
+    pub score: [TaperedScore; 2], // by color
+    pub passed: [Bitboard; 2],    // passed pawns by color
+}
+
+// Piece activity and king safety terms of one color, see pieces::piece_terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PieceTerms {
+    pub mobility: TaperedScore,
+    pub king_attack: TaperedScore, // against the opponent king
+    pub rook_files: TaperedScore,
+    pub rook_on_seventh: TaperedScore,
+    pub bishop_pair: TaperedScore,
+    pub outposts: TaperedScore,
+    pub trapped: TaperedScore,
+}
+
+// The white and the black part of an evaluation term
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EvalTerm {
+    pub white: TaperedScore,
+    pub black: TaperedScore,
+}
+
+/*
+The evaluation of a position split into its terms, see eval_trace.
+The score is the sum of all terms blended by the phase, from the view of the side to move.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalBreakdown {
+    pub active_player: Color,
+    pub phase: i32,
+    pub material: EvalTerm,
+    pub piece_squares: EvalTerm, // the piece-square tables without the material values
+    pub pawn_structure: EvalTerm,
+    pub passed_pawns: EvalTerm, // king distances and unstoppable pawns
+    pub pawn_shield: EvalTerm,
+    pub king_attack: EvalTerm, // against the opponent king
+    pub mobility: EvalTerm,
+    pub rook_files: EvalTerm,
+    pub rook_on_seventh: EvalTerm,
+    pub bishop_pair: EvalTerm,
+    pub outposts: EvalTerm,
+    pub trapped: EvalTerm,
+    pub king_edge: EvalTerm, // the enemy king driven to the edge, for the side ahead in material
+    pub check: EvalTerm,
+    pub score: Score,
+}
+
+// Direct-mapped cache of pawn structure evaluations
+#[derive(Clone)]
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+pub const PAWN_TABLE_ENTRIES: usize = 1 << 14;
+
+// A middlegame and an endgame score, blended by the game phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaperedScore {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+// centipawns, the king is never captured
+pub fn piece_value(piece: PieceType) -> i32 {
+    match piece {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 300,
+        PieceType::Bishop => 300,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        PieceType::King => 0,
     }
-    material_balance
-}
-
-fn get_knight_value(square: Square) -> f32 {
-    let dx = min(square.0 as i32, 7 - square.0 as i32);
-    let dy = min(square.0 as i32, 7 - square.0 as i32);
-    let num_jumps = match (dx, dy) {
-        (0, 0) => 2,
-        (1, 0) | (0, 1) => 3,
-        (1, 1) | (0, 2) | (2, 0) | (0, 3) | (3, 0) => 4,
-        (1, 2) | (2, 1) | (1, 3) | (3, 1) => 6,
-        _ => 8,
-    };
-    250.0 + 10.0 * num_jumps as f32
-}
-
-fn middlegame_bonuses(board: &Board) -> f32 {
-    let active_player = board.active_player;
-    // king safety bonus
-    let mut score = 0.0;
-    let king_sq = seek_king(board, active_player);
-    // penalize king in center
-    match king_sq.0 {
-        File::E | File::D => score -= 20.0,
-        File::F => score -= 10.0,
-        _ => score += 20.0,
+}
+
+pub fn get_material_eval(board: &Board) -> Score {
+    let mut material_balance = 0;
+    for piece in [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        let value = piece_value(piece);
+        let my_count = board.piece_bb(Piece(piece, board.active_player)).count() as i32;
+        let opp_count = board
+            .piece_bb(Piece(piece, board.active_player.opponent()))
+            .count() as i32;
+        material_balance += value * (my_count - opp_count);
     }
-    // pawn shield bonus (king on back rank, at least 2 pawns in front)
-    if king_sq.1 == board.active_player.home_rank() {
-        let mut pawn_shield = 0;
-        for pos in [(-1, 0), (0, 0), (1, 0)]
-            .iter()
-            .filter_map(|dir| pos_plus(Square(king_sq.0, active_player.pawn_start_rank()), *dir))
-        {
-            if let Some(Piece(PieceType::Pawn, owner)) = board.get_piece_at(pos) {
-                if owner == board.active_player {
-                    pawn_shield += 1;
-                }
-            }
-        }
-        if pawn_shield == 1 {
-            score += 30.0;
-        }
-        if pawn_shield >= 2 {
-            score += 100.0;
-        }
+    Score(material_balance)
+}
+
+impl TaperedScore {
+    pub fn new(mg: i32, eg: i32) -> TaperedScore {
+        TaperedScore { mg, eg }
+    }
+
+    // phase: MAX_PHASE with all pieces on the board, 0 with only kings and pawns
+    pub fn interpolate(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
     }
-    score
-}
-
-fn endgame_bonuses(board: &Board) -> f32 {
-    // if endgame -> enemy king near edge bonus
-    let mut score = 0.0;
-    let king_sq = seek_king(board, board.active_player.opponent());
-    // distance to edge
-    let dx = min(king_sq.0 as i32, 7 - king_sq.0 as i32);
-    let dy = min(king_sq.0 as i32, 7 - king_sq.0 as i32);
-    score += (3.0 - min(dx, dy) as f32) * 10.0;
-    score
-}
-
-fn get_pawn_value(square: Square, color: Color) -> f32 {
-    let rank = square.1;
-    let home_rank = color.pawn_start_rank();
-    let dist_bonus = match (rank as i32 - home_rank as i32).abs() {
-        0 => 0.0,
-        1 => 10.0,
-        2 => 20.0,
-        3 => 30.0,
-        4 => 40.0,
-        _ => 220.0, // about to promote, extremely valuable
-    };
-    // middle pawns are more valuable
-    let file = square.0;
-    let file_bonus = match file {
-        File::A | File::H => 0.0,
-        File::B | File::G => 5.0,
-        File::C | File::F => 10.0,
-        File::D | File::E => 20.0,
-    };
-    100.0 + dist_bonus + file_bonus
 }
 
-// score in centipawns
-pub fn smart_eval(board: &Board) -> f32 {
-    let mut score = 0.0;
-    // check penalty
-    let in_check = is_king_in_check(board);
-    if in_check {
-        score -= 30.0;
+impl Add for TaperedScore {
+    type Output = TaperedScore;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        TaperedScore::new(self.mg + rhs.mg, self.eg + rhs.eg)
     }
-    let mut my_material = 0.0;
-    let mut opp_material = 0.0;
-    for sq in SquareIter::new() {
-        if let Some(Piece(piece, owner)) = board.get_piece_at(sq) {
-            let value = match piece {
-                PieceType::Pawn => get_pawn_value(sq, owner),
-                PieceType::Knight => get_knight_value(sq),
-                PieceType::Bishop => 310.0,
-                PieceType::Rook => 500.0,
-                PieceType::Queen => 900.0,
-                PieceType::King => 0.0,
-            };
-            if owner == board.active_player {
-                my_material += value;
-            } else {
-                opp_material += value;
-            }
+}
+
+impl Sub for TaperedScore {
+    type Output = TaperedScore;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        TaperedScore::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl Mul<i32> for TaperedScore {
+    type Output = TaperedScore;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        TaperedScore::new(self.mg * rhs, self.eg * rhs)
+    }
+}
+
+impl Neg for TaperedScore {
+    type Output = TaperedScore;
+
+    fn neg(self) -> Self::Output {
+        TaperedScore::new(-self.mg, -self.eg)
+    }
+}
+
+impl AddAssign for TaperedScore {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for TaperedScore {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+// Remaining non-pawn material, weighted by PHASE_WEIGHTS and capped at MAX_PHASE (after early promotions)
+pub fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ]
+    .iter()
+    .map(|piece| board.pieces(*piece).count() as i32 * PHASE_WEIGHTS[*piece as usize])
+    .sum();
+    phase.min(MAX_PHASE)
+}
+
+// bonus for pawns in front of a king on its home rank, only matters in the middlegame
+fn pawn_shield(board: &Board, color: Color) -> i32 {
+    let king_sq = seek_king(board, color);
+    if king_sq.1 != color.home_rank() {
+        return 0;
+    }
+    let pawn_shield = [(-1, 0), (0, 0), (1, 0)]
+        .iter()
+        .filter_map(|dir| pos_plus(Square(king_sq.0, color.pawn_start_rank()), *dir))
+        .filter(|pos| board.get_piece_at(*pos) == Some(Piece(PieceType::Pawn, color)))
+        .count();
+    match pawn_shield {
+        0 => 0,
+        1 => 30,
+        _ => 100,
+    }
+}
+
+// Endgame bonus for the side ahead in material when the enemy king is near the edge, where it can be mated
+fn king_edge(board: &Board, color: Color) -> TaperedScore {
+    let material = |color: Color| -> i32 {
+        [
+            PieceType::Pawn,
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ]
+        .iter()
+        .map(|piece| board.piece_bb(Piece(*piece, color)).count() as i32 * piece_value(*piece))
+        .sum()
+    };
+    if material(color) <= material(color.opponent()) {
+        return TaperedScore::default();
+    }
+    let Square(file, rank) = seek_king(board, color.opponent());
+    let dx = (file as i32).min(7 - file as i32);
+    let dy = (rank as i32).min(7 - rank as i32);
+    TaperedScore::new(0, (3 - dx.min(dy)) * KING_EDGE)
+}
+
+// Every term of the evaluation with the given piece-square tables and the pawn structure of board
+pub fn trace(board: &Board, tables: &PieceSquareTables, pawns: PawnEntry) -> EvalBreakdown {
+    let mut material = EvalTerm::default();
+    let mut piece_squares = EvalTerm::default();
+    for sq in board.occupancy() {
+        if let Some(piece @ Piece(piece_type, color)) = board.get_piece_at(sq) {
+            let value = tables.material[piece_type as usize];
+            material.add(color, value);
+            piece_squares.add(color, tables.get(piece, sq) - value);
         }
     }
-    // TODO score pawns differently depending on phase
-    score += my_material - opp_material;
-    let is_endgame = my_material + opp_material < 3300.0;
-    // king safety bonus
-    score += if is_endgame {
-        endgame_bonuses(board)
-    } else {
-        middlegame_bonuses(board)
+    let [white_passed, black_passed] = pawns::passed_pawn_bonus(board, pawns.passed);
+    let [white, black] =
+        [Color::White, Color::Black].map(|color| pieces::piece_terms(board, color));
+    let piece_term =
+        |term: fn(&PieceTerms) -> TaperedScore| EvalTerm::new(term(&white), term(&black));
+    let [white_shield, black_shield] =
+        [Color::White, Color::Black].map(|color| TaperedScore::new(pawn_shield(board, color), 0));
+    let [white_edge, black_edge] =
+        [Color::White, Color::Black].map(|color| king_edge(board, color));
+    let mut check = EvalTerm::default();
+    if is_king_in_check(board) {
+        check.add(board.active_player, CHECK_PENALTY);
+    }
+    let mut trace = EvalBreakdown {
+        active_player: board.active_player,
+        phase: game_phase(board),
+        material,
+        piece_squares,
+        pawn_structure: EvalTerm::new(pawns.score[0], pawns.score[1]),
+        passed_pawns: EvalTerm::new(white_passed, black_passed),
+        pawn_shield: EvalTerm::new(white_shield, black_shield),
+        king_attack: piece_term(|terms| terms.king_attack),
+        mobility: piece_term(|terms| terms.mobility),
+        rook_files: piece_term(|terms| terms.rook_files),
+        rook_on_seventh: piece_term(|terms| terms.rook_on_seventh),
+        bishop_pair: piece_term(|terms| terms.bishop_pair),
+        outposts: piece_term(|terms| terms.outposts),
+        trapped: piece_term(|terms| terms.trapped),
+        king_edge: EvalTerm::new(white_edge, black_edge),
+        check,
+        score: Score(0),
     };
-    // open file bonus
+    let score = trace.total().interpolate(trace.phase);
+    trace.score = Score(match board.active_player {
+        Color::White => score,
+        Color::Black => -score,
+    });
+    trace
+}
+
+// Every term of the evaluation with the given piece-square tables, without a pawn table
+pub fn trace_with_tables(board: &Board, tables: &PieceSquareTables) -> EvalBreakdown {
+    trace(board, tables, pawns::pawn_structure(board))
+}
+
+// Every term of smart_eval, to see why it likes a position
+pub fn eval_trace(board: &Board) -> EvalBreakdown {
+    trace_with_tables(board, PieceSquareTables::default_tables())
+}
 
-    score
+// Evaluation with the given piece-square tables, from the view of the side to move
+pub fn evaluate_with_tables(board: &Board, tables: &PieceSquareTables) -> Score {
+    trace_with_tables(board, tables).score
 }
 
-// TODO: figure out why knight into corner is best move in N7/7p/4k1p1/p3pp2/1b4Pr/5P2/6KP/1R6 b - - 1 37
+pub fn smart_eval(board: &Board) -> Score {
+    evaluate_with_tables(board, PieceSquareTables::default_tables())
+}
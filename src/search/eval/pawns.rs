@@ -0,0 +1,208 @@
+use crate::{
+    board::{
+        attacks::pawn_attacks,
+        bitboard::Bitboard,
+        model_utils::ColorProps,
+        models::{Color, File, Piece, PieceType, Rank, Square},
+        move_checking::seek_king,
+        Board,
+    },
+    hashing::get_pawn_hash,
+};
+
+use super::{PawnEntry, PawnTable, TaperedScore};
+
+#[cfg(test)]
+mod tests;
+
+// (middlegame, endgame) per pawn
+const DOUBLED: TaperedScore = TaperedScore { mg: -10, eg: -25 }; // for each pawn behind another one of its color
+const ISOLATED: TaperedScore = TaperedScore { mg: -10, eg: -15 };
+const BACKWARD: TaperedScore = TaperedScore { mg: -8, eg: -12 };
+// by rank from the pawn's side, rank 1 first
+const CONNECTED: [TaperedScore; 8] = [
+    TaperedScore { mg: 0, eg: 0 },
+    TaperedScore { mg: 0, eg: 0 },
+    TaperedScore { mg: 7, eg: 4 },
+    TaperedScore { mg: 8, eg: 5 },
+    TaperedScore { mg: 12, eg: 9 },
+    TaperedScore { mg: 25, eg: 20 },
+    TaperedScore { mg: 45, eg: 40 },
+    TaperedScore { mg: 0, eg: 0 },
+];
+const PASSED: [TaperedScore; 8] = [
+    TaperedScore { mg: 0, eg: 0 },
+    TaperedScore { mg: 5, eg: 10 },
+    TaperedScore { mg: 5, eg: 15 },
+    TaperedScore { mg: 10, eg: 25 },
+    TaperedScore { mg: 20, eg: 45 },
+    TaperedScore { mg: 35, eg: 75 },
+    TaperedScore { mg: 60, eg: 120 },
+    TaperedScore { mg: 0, eg: 0 },
+];
+// endgame bonus per rank above the 4th and square of distance, see passed_pawn_bonus
+const OPPONENT_KING_DISTANCE: i32 = 5;
+const OWN_KING_DISTANCE: i32 = 2;
+// a pawn the king cannot catch in a pawn endgame will promote, worth almost a queen
+const UNSTOPPABLE: i32 = 700;
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+
+pub(super) fn file_mask(file: File) -> Bitboard {
+    Bitboard(FILE_A << file as u32)
+}
+
+pub(super) fn adjacent_files(file: File) -> Bitboard {
+    let file = file as i8;
+    [file - 1, file + 1]
+        .into_iter()
+        .filter_map(File::from_i8)
+        .fold(Bitboard::EMPTY, |files, file| files | file_mask(file))
+}
+
+pub(super) fn rank_mask(rank: Rank) -> Bitboard {
+    Bitboard(0xFF << (8 * rank as u32))
+}
+
+// all ranks in front of rank, seen from color
+pub(super) fn ranks_ahead(color: Color, rank: Rank) -> Bitboard {
+    match color {
+        Color::White if rank == Rank::_8 => Bitboard::EMPTY,
+        Color::White => Bitboard(!0 << (8 * (rank as u32 + 1))),
+        Color::Black => Bitboard((1 << (8 * rank as u32)) - 1),
+    }
+}
+
+// rank counted from color's side, 0 for its home rank
+pub(super) fn relative_rank(color: Color, rank: Rank) -> usize {
+    match color {
+        Color::White => rank as usize,
+        Color::Black => 7 - rank as usize,
+    }
+}
+
+fn distance(a: Square, b: Square) -> i32 {
+    let files = (a.0 as i32 - b.0 as i32).abs();
+    let ranks = (a.1 as i32 - b.1 as i32).abs();
+    files.max(ranks)
+}
+
+// the square in front of a pawn that is not on its last rank
+pub(super) fn square_ahead(color: Color, Square(file, rank): Square) -> Square {
+    let rank = match color {
+        Color::White => rank as i8 + 1,
+        Color::Black => rank as i8 - 1,
+    };
+    Square(file, Rank::from_i8(rank).unwrap())
+}
+
+/*
+Pawn structure terms of one color:
+doubled: a pawn with another pawn of its color in front of it on the same file
+isolated: no pawns of its color on the adjacent files
+backward: not isolated, but all pawns on the adjacent files are further advanced,
+    and an opponent pawn controls the square in front of it, so it cannot advance safely
+connected: defended by a pawn or side by side with one
+passed: no opponent pawns in front of it on its file or the adjacent files, and not doubled
+*/
+fn evaluate_pawns(board: &Board, color: Color) -> (TaperedScore, Bitboard) {
+    let own_pawns = board.piece_bb(Piece(PieceType::Pawn, color));
+    let opponent_pawns = board.piece_bb(Piece(PieceType::Pawn, color.opponent()));
+    let mut score = TaperedScore::default();
+    let mut passed = Bitboard::EMPTY;
+    for square @ Square(file, rank) in own_pawns {
+        let ahead = ranks_ahead(color, rank);
+        let neighbours = own_pawns & adjacent_files(file);
+        let doubled = !(own_pawns & file_mask(file) & ahead).is_empty();
+        let supported = !(own_pawns & pawn_attacks(color.opponent(), square)).is_empty();
+        let phalanx = !(neighbours & rank_mask(rank)).is_empty();
+        let is_passed = !doubled
+            && (opponent_pawns & (file_mask(file) | adjacent_files(file)) & ahead).is_empty();
+        if doubled {
+            score += DOUBLED;
+        }
+        if neighbours.is_empty() {
+            score += ISOLATED;
+        } else if supported || phalanx {
+            score += CONNECTED[relative_rank(color, rank)];
+        } else if !is_passed && (neighbours & !ahead).is_empty() {
+            let stop_square = square_ahead(color, square);
+            if !(opponent_pawns & pawn_attacks(color, stop_square)).is_empty() {
+                score += BACKWARD;
+            }
+        }
+        if is_passed {
+            score += PASSED[relative_rank(color, rank)];
+            passed |= Bitboard::from_square(square);
+        }
+    }
+    (score, passed)
+}
+
+pub fn pawn_structure(board: &Board) -> PawnEntry {
+    let (white_score, white_passed) = evaluate_pawns(board, Color::White);
+    let (black_score, black_passed) = evaluate_pawns(board, Color::Black);
+    PawnEntry {
+        key: get_pawn_hash(board),
+        score: [white_score, black_score],
+        passed: [white_passed, black_passed],
+    }
+}
+
+/*
+Passed pawn terms of each color that depend on more than the pawns, so they cannot be cached.
+In the endgame, a passed pawn is stronger the further the opponent king and the closer the own king is to the
+square in front of it, more so the further it is advanced.
+In a pawn endgame, a pawn outside the square of the opponent king promotes (rule of the square).
+*/
+pub fn passed_pawn_bonus(board: &Board, passed: [Bitboard; 2]) -> [TaperedScore; 2] {
+    let mut score = [0; 2];
+    for color in [Color::White, Color::Black] {
+        let own_king = seek_king(board, color);
+        let opponent_king = seek_king(board, color.opponent());
+        let pawn_endgame = !board.has_non_pawn_material(color.opponent());
+        let mut unstoppable = false;
+        for square in passed[color as usize] {
+            let rank = relative_rank(color, square.1);
+            let stop_square = square_ahead(color, square);
+            if rank >= 3 {
+                score[color as usize] += (rank as i32 - 2)
+                    * (OPPONENT_KING_DISTANCE * distance(opponent_king, stop_square)
+                        - OWN_KING_DISTANCE * distance(own_king, stop_square));
+            }
+            if pawn_endgame {
+                let promotion_square = Square(square.0, color.opp_home_rank());
+                let path = file_mask(square.0) & ranks_ahead(color, square.1);
+                let moves_to_promote = 7 - rank.max(2) as i32; // a pawn on its start rank can move two squares
+                let tempo = i32::from(board.active_player != color); // the opponent moves first
+                if (path & board.occupancy()).is_empty()
+                    && distance(opponent_king, promotion_square) - tempo > moves_to_promote
+                {
+                    unstoppable = true;
+                }
+            }
+        }
+        if unstoppable {
+            score[color as usize] += UNSTOPPABLE;
+        }
+    }
+    score.map(|score| TaperedScore::new(0, score))
+}
+
+impl PawnTable {
+    pub fn new(entries: usize) -> PawnTable {
+        PawnTable {
+            entries: vec![PawnEntry::default(); entries.max(1)],
+        }
+    }
+
+    // the pawn structure of board with pawn key key (see hashing::get_pawn_hash), from the table if it was evaluated before
+    pub fn probe(&mut self, board: &Board, key: u64) -> PawnEntry {
+        let index = (key % self.entries.len() as u64) as usize;
+        // the empty entry has key 0, which is also the correct entry for a board without pawns
+        if self.entries[index].key != key {
+            self.entries[index] = pawn_structure(board);
+        }
+        self.entries[index]
+    }
+}
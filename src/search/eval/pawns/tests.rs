@@ -0,0 +1,101 @@
+use rstest::rstest;
+
+use crate::{
+    board::{bitboard::Bitboard, models::Square, Board},
+    hashing::get_pawn_hash,
+};
+
+use super::{
+    passed_pawn_bonus, pawn_structure, PawnTable, TaperedScore, BACKWARD, CONNECTED, DOUBLED,
+    ISOLATED, PASSED, UNSTOPPABLE,
+};
+
+fn squares(squares: &[&str]) -> Bitboard {
+    squares
+        .iter()
+        .map(|square| Bitboard::from_square(Square::from_string(square).unwrap()))
+        .fold(Bitboard::EMPTY, |bitboard, square| bitboard | square)
+}
+
+#[rstest]
+// doubled isolated pawns, only the front one is passed
+#[case("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1", DOUBLED + ISOLATED + ISOLATED + PASSED[2])]
+// c3 cannot advance past d5, d4 is defended by it, d5 is isolated
+#[case(
+    "4k3/8/8/3p4/3P4/2P5/8/4K3 w - - 0 1",
+    BACKWARD + CONNECTED[3] - ISOLATED
+)]
+// a phalanx of passed pawns for black
+#[case(
+    "4k3/8/8/8/3pp3/8/8/4K3 w - - 0 1",
+    -(CONNECTED[4] + CONNECTED[4] + PASSED[4] + PASSED[4])
+)]
+#[case(
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    TaperedScore::default()
+)]
+fn test_pawn_structure(#[case] fen: &str, #[case] expected: TaperedScore) {
+    let board = Board::from_fen(fen).unwrap();
+
+    let [white, black] = pawn_structure(&board).score;
+
+    assert_eq!(white - black, expected);
+}
+
+#[test]
+fn test_passed_pawns() {
+    let board = Board::from_fen("4k3/5p2/8/P2p4/6P1/8/8/4K3 w - - 0 1").unwrap();
+    let entry = pawn_structure(&board);
+
+    assert_eq!(entry.passed, [squares(&["a5"]), squares(&["d5"])]);
+}
+
+#[rstest]
+// the black king is outside the square of the pawn
+#[case("8/8/8/P7/8/8/8/4k1K1 w - - 0 1", true)]
+// the black king reaches the square only if it moves first
+#[case("8/8/8/P7/4k3/8/8/6K1 w - - 0 1", true)]
+#[case("8/8/8/P7/4k3/8/8/6K1 b - - 0 1", false)]
+#[case("k7/8/8/P7/8/8/8/6K1 w - - 0 1", false)]
+// a knight can stop the pawn
+#[case("8/8/8/P7/8/8/8/4k1Kn w - - 0 1", false)]
+// from the start rank, the pawn moves two squares at once
+#[case("8/8/8/8/8/8/P7/5k1K w - - 0 1", true)]
+fn test_unstoppable_pawn(#[case] fen: &str, #[case] unstoppable: bool) {
+    let board = Board::from_fen(fen).unwrap();
+    let [white, black] = passed_pawn_bonus(&board, pawn_structure(&board).passed);
+    let bonus = white - black;
+
+    assert_eq!(bonus.eg >= UNSTOPPABLE, unstoppable);
+    assert_eq!(bonus.mg, 0);
+}
+
+#[test]
+fn test_passed_pawn_wants_opponent_king_far() {
+    let eval = |fen: &str| {
+        let board = Board::from_fen(fen).unwrap();
+        let [white, black] = passed_pawn_bonus(&board, pawn_structure(&board).passed);
+        (white - black).eg
+    };
+
+    assert!(eval("8/8/4k3/3P4/8/8/8/1K1r4 w - - 0 1") < eval("8/8/8/3P4/8/8/8/1K1r2k1 w - - 0 1"));
+    assert!(eval("8/8/4k3/3P4/8/8/8/1K1r4 w - - 0 1") < eval("8/8/2K1k3/3P4/8/8/8/3r4 w - - 0 1"));
+}
+
+#[test]
+fn test_pawn_table_caches_by_pawns() {
+    let mut table = PawnTable::new(64);
+    let board = Board::from_fen("4k3/8/8/3p4/3P4/2P5/8/4K3 w - - 0 1").unwrap();
+    let moved_kings = Board::from_fen("8/3k4/8/3p4/3P4/2P5/5K2/8 b - - 0 1").unwrap();
+
+    let entry = table.probe(&board, get_pawn_hash(&board));
+    assert_eq!(entry, pawn_structure(&board));
+    assert_eq!(
+        table.probe(&moved_kings, get_pawn_hash(&moved_kings)),
+        entry
+    );
+    assert_eq!(
+        table.probe(&Board::default(), get_pawn_hash(&Board::default())),
+        pawn_structure(&Board::default())
+    );
+}
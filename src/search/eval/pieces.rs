@@ -0,0 +1,170 @@
+use crate::board::{
+    attacks::{king_attacks, pawn_attacks, piece_attacks},
+    bitboard::Bitboard,
+    model_utils::ColorProps,
+    models::{Color, File, Piece, PieceType, Square},
+    move_checking::seek_king,
+    Board,
+};
+
+use super::{
+    pawns::{adjacent_files, file_mask, rank_mask, ranks_ahead, relative_rank, square_ahead},
+    PieceTerms, TaperedScore,
+};
+
+#[cfg(test)]
+mod tests;
+
+// per safe square a piece attacks more than MOBILITY_BASE, indexed by PieceType
+const MOBILITY: [TaperedScore; 6] = [
+    TaperedScore { mg: 0, eg: 0 },
+    TaperedScore { mg: 4, eg: 4 },
+    TaperedScore { mg: 5, eg: 5 },
+    TaperedScore { mg: 2, eg: 4 },
+    TaperedScore { mg: 1, eg: 2 },
+    TaperedScore { mg: 0, eg: 0 },
+];
+const MOBILITY_BASE: [i32; 6] = [0, 4, 7, 7, 14, 0];
+// attack units per attacked square of the opponent king zone, indexed by PieceType
+const KING_ATTACK_WEIGHT: [i32; 6] = [0, 2, 2, 3, 5, 0];
+const MAX_KING_ATTACK: i32 = 500;
+const ROOK_OPEN_FILE: TaperedScore = TaperedScore { mg: 40, eg: 20 };
+const ROOK_SEMI_OPEN_FILE: TaperedScore = TaperedScore { mg: 20, eg: 10 };
+const ROOK_ON_SEVENTH: TaperedScore = TaperedScore { mg: 20, eg: 40 };
+const BISHOP_PAIR: TaperedScore = TaperedScore { mg: 30, eg: 50 };
+const KNIGHT_OUTPOST: TaperedScore = TaperedScore { mg: 25, eg: 15 };
+const TRAPPED_BISHOP: TaperedScore = TaperedScore { mg: -100, eg: -100 };
+const TRAPPED_ROOK: TaperedScore = TaperedScore { mg: -50, eg: -10 };
+
+impl PieceTerms {
+    pub fn total(&self) -> TaperedScore {
+        self.mobility
+            + self.king_attack
+            + self.rook_files
+            + self.rook_on_seventh
+            + self.bishop_pair
+            + self.outposts
+            + self.trapped
+    }
+}
+
+fn pawn_attack_span(board: &Board, color: Color) -> Bitboard {
+    board
+        .piece_bb(Piece(PieceType::Pawn, color))
+        .into_iter()
+        .fold(Bitboard::EMPTY, |attacks, square| {
+            attacks | pawn_attacks(color, square)
+        })
+}
+
+// a knight on the 4th to 6th rank, defended by a pawn and out of reach of opponent pawns
+fn is_outpost(board: &Board, color: Color, square @ Square(file, rank): Square) -> bool {
+    let own_pawns = board.piece_bb(Piece(PieceType::Pawn, color));
+    let opponent_pawns = board.piece_bb(Piece(PieceType::Pawn, color.opponent()));
+    (3..=5).contains(&relative_rank(color, rank))
+        && !(own_pawns & pawn_attacks(color.opponent(), square)).is_empty()
+        && (opponent_pawns & adjacent_files(file) & ranks_ahead(color, rank)).is_empty()
+}
+
+// a bishop that took the pawn on a7 (or h7) and is cut off by the pawn on b6 (or g6)
+fn is_trapped_bishop(board: &Board, color: Color, Square(file, rank): Square) -> bool {
+    let pawn_file = match file {
+        File::A => File::B,
+        File::H => File::G,
+        _ => return false,
+    };
+    relative_rank(color, rank) == 6
+        && board.get_piece_at(square_ahead(color.opponent(), Square(pawn_file, rank)))
+            == Some(Piece(PieceType::Pawn, color.opponent()))
+}
+
+// a rook in the corner behind its king, which can no longer castle to free it
+fn is_trapped_rook(board: &Board, color: Color, square: Square, mobility: i32) -> bool {
+    let king = seek_king(board, color);
+    if mobility > 3 || square.1 != color.home_rank() || king.1 != color.home_rank() {
+        return false;
+    }
+    let (rook_file, king_file) = (square.0 as i8, king.0 as i8);
+    let kingside = king_file >= File::E as i8 && rook_file > king_file;
+    let queenside = king_file <= File::D as i8 && rook_file < king_file;
+    (kingside && !board.has_kingside_castling_rights(color))
+        || (queenside && !board.has_queenside_castling_rights(color))
+}
+
+/*
+Piece activity and king safety terms of one color:
+mobility: squares attacked by each piece that are neither occupied by its own pieces nor attacked by opponent pawns,
+    compared to a typical number for the piece
+king_attack: with at least two pieces attacking the squares around the opponent king, grows with the square of the
+    attack units, so that coordinated attacks count more than their parts
+rook_files: rooks on files without pawns, or without pawns of their own color
+rook_on_seventh: rooks on the 7th rank attacking pawns there or cutting off the king on the 8th
+bishop_pair: two or more bishops
+outposts: knights on squares that opponent pawns can never attack, see is_outpost
+trapped: bishops and rooks without a way out, see is_trapped_bishop and is_trapped_rook
+*/
+pub fn piece_terms(board: &Board, color: Color) -> PieceTerms {
+    let opponent = color.opponent();
+    let occupancy = board.occupancy();
+    let pawns = board.pieces(PieceType::Pawn);
+    let own_pawns = board.piece_bb(Piece(PieceType::Pawn, color));
+    let opponent_pawns = board.piece_bb(Piece(PieceType::Pawn, opponent));
+    let opponent_king = seek_king(board, opponent);
+    let mobility_area = !board.color_pieces(color) & !pawn_attack_span(board, opponent);
+    let king_zone = king_attacks(opponent_king) | Bitboard::from_square(opponent_king);
+    let mut terms = PieceTerms::default();
+    let mut attackers = 0;
+    let mut attack_units = 0;
+    for piece in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        for square in board.piece_bb(Piece(piece, color)) {
+            let attacks = piece_attacks(Piece(piece, color), square, occupancy);
+            let mobility = (attacks & mobility_area).count() as i32;
+            terms.mobility += MOBILITY[piece as usize] * (mobility - MOBILITY_BASE[piece as usize]);
+            let zone_attacks = attacks & king_zone;
+            if !zone_attacks.is_empty() {
+                attackers += 1;
+                attack_units += KING_ATTACK_WEIGHT[piece as usize] * zone_attacks.count() as i32;
+            }
+            match piece {
+                PieceType::Knight if is_outpost(board, color, square) => {
+                    terms.outposts += KNIGHT_OUTPOST
+                }
+                PieceType::Bishop if is_trapped_bishop(board, color, square) => {
+                    terms.trapped += TRAPPED_BISHOP
+                }
+                PieceType::Rook => {
+                    if (pawns & file_mask(square.0)).is_empty() {
+                        terms.rook_files += ROOK_OPEN_FILE;
+                    } else if (own_pawns & file_mask(square.0)).is_empty() {
+                        terms.rook_files += ROOK_SEMI_OPEN_FILE;
+                    }
+                    if relative_rank(color, square.1) == 6
+                        && (relative_rank(color, opponent_king.1) == 7
+                            || !(opponent_pawns & rank_mask(square.1)).is_empty())
+                    {
+                        terms.rook_on_seventh += ROOK_ON_SEVENTH;
+                    }
+                    if is_trapped_rook(board, color, square, mobility) {
+                        terms.trapped += TRAPPED_ROOK;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    if attackers >= 2 {
+        terms.king_attack = TaperedScore::new(
+            (attack_units * attack_units / 2).min(MAX_KING_ATTACK),
+            attack_units,
+        );
+    }
+    if board.piece_bb(Piece(PieceType::Bishop, color)).count() >= 2 {
+        terms.bishop_pair = BISHOP_PAIR;
+    }
+    terms
+}
@@ -0,0 +1,86 @@
+use rstest::rstest;
+
+use crate::board::{models::Color, Board};
+
+use super::{
+    piece_terms, PieceTerms, TaperedScore, BISHOP_PAIR, KNIGHT_OUTPOST, ROOK_ON_SEVENTH,
+    ROOK_OPEN_FILE, ROOK_SEMI_OPEN_FILE, TRAPPED_BISHOP, TRAPPED_ROOK,
+};
+
+fn white_terms(fen: &str) -> PieceTerms {
+    piece_terms(&Board::from_fen(fen).unwrap(), Color::White)
+}
+
+#[rstest]
+#[case("4k3/pppp1ppp/8/8/8/8/PPPP1PPP/4RK2 w - - 0 1", ROOK_OPEN_FILE)]
+#[case("4k3/pppppppp/8/8/8/8/PPPP1PPP/4RK2 w - - 0 1", ROOK_SEMI_OPEN_FILE)]
+#[case(
+    "4k3/pppppppp/8/8/8/8/PPPPPPPP/4RK2 w - - 0 1",
+    TaperedScore::default()
+)]
+fn test_rook_files(#[case] fen: &str, #[case] expected: TaperedScore) {
+    assert_eq!(white_terms(fen).rook_files, expected);
+}
+
+#[rstest]
+#[case("6k1/R4ppp/8/8/8/8/5PPP/6K1 w - - 0 1", ROOK_ON_SEVENTH)] // attacks pawns
+#[case("6k1/R7/8/8/8/8/5PPP/6K1 w - - 0 1", ROOK_ON_SEVENTH)] // cuts off the king
+#[case("8/R7/6k1/8/8/8/5PPP/6K1 w - - 0 1", TaperedScore::default())]
+fn test_rook_on_seventh(#[case] fen: &str, #[case] expected: TaperedScore) {
+    assert_eq!(white_terms(fen).rook_on_seventh, expected);
+}
+
+#[rstest]
+#[case("4k3/2p5/8/4N3/3P4/8/8/4K3 w - - 0 1", KNIGHT_OUTPOST)]
+#[case("4k3/3p4/8/4N3/3P4/8/8/4K3 w - - 0 1", TaperedScore::default())] // d7-d6 chases it away
+#[case("4k3/2p5/8/4N3/8/8/8/4K3 w - - 0 1", TaperedScore::default())] // not defended
+#[case("4k3/2p5/8/8/8/4N3/3P4/4K3 w - - 0 1", TaperedScore::default())] // not advanced
+fn test_knight_outposts(#[case] fen: &str, #[case] expected: TaperedScore) {
+    assert_eq!(white_terms(fen).outposts, expected);
+}
+
+#[rstest]
+#[case("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1", TRAPPED_BISHOP)]
+#[case("4k3/B7/8/1p6/8/8/8/4K3 w - - 0 1", TaperedScore::default())]
+#[case("4k3/8/8/8/8/8/6PP/5K1R w - - 0 1", TRAPPED_ROOK)]
+#[case("4k3/8/8/8/8/8/6PP/4K2R w K - 0 1", TaperedScore::default())] // castling frees it
+#[case("4k3/8/8/8/8/8/6P1/5K1R w - - 0 1", TaperedScore::default())] // the h-file is open
+fn test_trapped_pieces(#[case] fen: &str, #[case] expected: TaperedScore) {
+    assert_eq!(white_terms(fen).trapped, expected);
+}
+
+#[test]
+fn test_bishop_pair() {
+    assert_eq!(
+        white_terms("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").bishop_pair,
+        BISHOP_PAIR
+    );
+    assert_eq!(
+        white_terms("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1").bishop_pair,
+        TaperedScore::default()
+    );
+}
+
+#[test]
+fn test_mobility() {
+    let corner = white_terms("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").mobility;
+    let center = white_terms("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").mobility;
+    // squares attacked by pawns are not safe
+    let chased = white_terms("4k3/8/2p5/8/3N4/8/8/4K3 w - - 0 1").mobility;
+
+    assert!(corner.mg < chased.mg);
+    assert!(chased.mg < center.mg);
+}
+
+#[test]
+fn test_king_attack_needs_two_attackers() {
+    let knight = white_terms("6k1/5ppp/8/6N1/8/8/5PPP/6K1 w - - 0 1");
+    let knight_and_queen = white_terms("6k1/5ppp/8/6NQ/8/8/5PPP/6K1 w - - 0 1");
+
+    assert_eq!(knight.king_attack, TaperedScore::default());
+    assert!(knight_and_queen.king_attack.mg > 0);
+    assert_eq!(knight_and_queen.total().mg - knight.total().mg, {
+        let queen = knight_and_queen.mobility - knight.mobility;
+        queen.mg + knight_and_queen.king_attack.mg
+    });
+}
@@ -0,0 +1,100 @@
+use std::sync::OnceLock;
+
+use crate::board::models::{Color, Piece, Square};
+
+use super::{PieceSquareTables, TaperedScore};
+
+const DEFAULT_TABLES: &str = include_str!("pst.txt");
+const PIECE_NAMES: [&str; 6] = ["pawn", "knight", "bishop", "rook", "queen", "king"];
+
+// tokens: (line number, token)
+fn next_number<'a>(
+    tokens: &mut impl Iterator<Item = (usize, &'a str)>,
+    what: &str,
+) -> Result<i32, String> {
+    match tokens.next() {
+        Some((line, token)) => token
+            .parse()
+            .map_err(|_| format!("Line {}: expected {}, found {}", line, what, token)),
+        None => Err(format!("Unexpected end of tables, expected {}", what)),
+    }
+}
+
+impl Default for PieceSquareTables {
+    fn default() -> Self {
+        PieceSquareTables::default_tables().clone()
+    }
+}
+
+impl PieceSquareTables {
+    // the tables of src/search/eval/pst.txt, parsed once
+    pub fn default_tables() -> &'static PieceSquareTables {
+        static TABLES: OnceLock<PieceSquareTables> = OnceLock::new();
+        TABLES.get_or_init(|| {
+            let mut tables = PieceSquareTables {
+                mg: [[0; 64]; 6],
+                eg: [[0; 64]; 6],
+                material: [TaperedScore::default(); 6],
+            };
+            tables
+                .parse_overrides(DEFAULT_TABLES)
+                .expect("Invalid default piece-square tables");
+            tables
+        })
+    }
+
+    // The default tables with the sections of the file at path replacing theirs, see pst.txt for the format
+    pub fn load(path: &str) -> Result<PieceSquareTables, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read piece-square tables {}: {}", path, e))?;
+        let mut tables = PieceSquareTables::default();
+        tables.parse_overrides(&text)?;
+        Ok(tables)
+    }
+
+    // Replaces the tables of all sections in text. On error the tables are left unchanged.
+    pub fn parse_overrides(&mut self, text: &str) -> Result<(), String> {
+        let mut tables = self.clone();
+        let mut tokens = text.lines().enumerate().flat_map(|(line, content)| {
+            let content = content.split('#').next().unwrap_or("");
+            content
+                .split_whitespace()
+                .map(move |token| (line + 1, token))
+        });
+        while let Some((line, name)) = tokens.next() {
+            let piece = PIECE_NAMES
+                .iter()
+                .position(|piece| *piece == name)
+                .ok_or(format!("Line {}: unknown piece {}", line, name))?;
+            let (table, material) = match tokens.next() {
+                Some((_, "mg")) => (&mut tables.mg[piece], &mut tables.material[piece].mg),
+                Some((_, "eg")) => (&mut tables.eg[piece], &mut tables.material[piece].eg),
+                Some((line, phase)) => {
+                    return Err(format!("Line {}: unknown phase {}", line, phase))
+                }
+                None => return Err(format!("Line {}: missing phase for {}", line, name)),
+            };
+            let value = next_number(&mut tokens, "material value")?;
+            *material = value;
+            // rows from rank 8 to rank 1, the tables are indexed from a1
+            for rank in (0..8).rev() {
+                for file in 0..8 {
+                    table[rank * 8 + file] = value + next_number(&mut tokens, "bonus")?;
+                }
+            }
+        }
+        *self = tables;
+        Ok(())
+    }
+
+    pub fn get(&self, Piece(piece, color): Piece, square: Square) -> TaperedScore {
+        let index = match color {
+            Color::White => square.to_index(),
+            Color::Black => square.to_index() ^ 56, // mirrored vertically
+        };
+        TaperedScore {
+            mg: self.mg[piece as usize][index],
+            eg: self.eg[piece as usize][index],
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use rstest::rstest;
+
+use crate::board::{
+    models::{Color, File, Piece, PieceType, Rank, Square},
+    Board,
+};
+
+use super::{
+    eval_trace, game_phase, smart_eval, EvalTerm, PieceSquareTables, TaperedScore, MAX_PHASE,
+};
+
+// the same position with colors swapped and the board mirrored vertically
+fn mirrored_fen(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split(' ').collect();
+    let swap_case = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+    let ranks: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+    let active_player = if fields[1] == "w" { "b" } else { "w" };
+    let en_passant = match fields[3] {
+        "-" => "-".to_string(),
+        square if square.ends_with('3') => square.replace('3', "6"),
+        square => square.replace('6', "3"),
+    };
+    format!(
+        "{} {} {} {} {} {}",
+        ranks.join("/"),
+        active_player,
+        swap_case(fields[2]),
+        en_passant,
+        fields[4],
+        fields[5]
+    )
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 w kq - 0 1")]
+fn test_eval_is_symmetric(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let mirrored = Board::from_fen(&mirrored_fen(fen)).unwrap();
+
+    assert_eq!(smart_eval(&board), smart_eval(&mirrored));
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", MAX_PHASE)]
+#[case(
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    MAX_PHASE
+)]
+#[case("4k3/8/8/3p4/8/8/3P4/4K3 w - - 0 1", 0)]
+#[case("4k3/8/8/3r4/8/8/3N4/4K3 w - - 0 1", 3)]
+#[case("4k3/4p3/8/8/8/8/QQQQQQQ1/4K3 w - - 0 1", MAX_PHASE)] // capped after promotions
+fn test_game_phase(#[case] fen: &str, #[case] expected: i32) {
+    assert_eq!(game_phase(&Board::from_fen(fen).unwrap()), expected);
+}
+
+#[rstest]
+#[case(MAX_PHASE, 100)]
+#[case(0, -40)]
+#[case(MAX_PHASE / 2, 30)]
+fn test_interpolate(#[case] phase: i32, #[case] expected: i32) {
+    assert_eq!(TaperedScore::new(100, -40).interpolate(phase), expected);
+}
+
+#[test]
+fn test_king_centralization_depends_on_phase() {
+    // the king belongs to the corner in the middlegame and to the center in the endgame
+    let middlegame = |king_rows: &str| format!("rnbqkbnr/pppppppp/8/8/8/{} w - - 0 1", king_rows);
+    let endgame = |king_rows: &str| format!("4k3/pppppppp/8/8/8/{} w - - 0 1", king_rows);
+    let eval = |fen: String| smart_eval(&Board::from_fen(&fen).unwrap());
+    let (corner, center) = ("8/PPPPPPPP/RNBQ1BKR", "4K3/PPPPPPPP/RNBQ1B1R");
+    let (corner_pawns, center_pawns) = ("8/PPPPPPPP/6K1", "4K3/PPPPPPPP/8");
+
+    assert!(eval(middlegame(corner)) > eval(middlegame(center)));
+    assert!(eval(endgame(corner_pawns)) < eval(endgame(center_pawns)));
+}
+
+#[test]
+fn test_default_tables() {
+    let tables = PieceSquareTables::default_tables();
+    let e4 = Square(File::E, Rank::_4);
+    let e5 = Square(File::E, Rank::_5);
+
+    assert_eq!(
+        tables.get(Piece(PieceType::Knight, Color::White), e4),
+        TaperedScore::new(365, 297)
+    );
+    assert_eq!(
+        tables.get(Piece(PieceType::Knight, Color::Black), e5),
+        tables.get(Piece(PieceType::Knight, Color::White), e4)
+    );
+    assert_eq!(*tables, PieceSquareTables::default());
+}
+
+#[test]
+fn test_override_tables() {
+    let mut tables = PieceSquareTables::default();
+    let section = format!("# knights everywhere\nknight mg 300\n{}", "10 ".repeat(64));
+    tables.parse_overrides(&section).unwrap();
+
+    assert!(tables.mg[PieceType::Knight as usize]
+        .iter()
+        .all(|value| *value == 310));
+    assert_eq!(tables.eg, PieceSquareTables::default().eg);
+    assert_eq!(
+        tables.mg[PieceType::Bishop as usize],
+        PieceSquareTables::default().mg[PieceType::Bishop as usize]
+    );
+}
+
+#[rstest]
+#[case("knave mg 300")] // unknown piece
+#[case("knight og 300")] // unknown phase
+#[case("knight mg")]
+#[case("knight mg 300 1 2 3")] // too few bonuses
+#[case("knight mg 300 x")]
+fn test_invalid_tables_are_rejected(#[case] text: &str) {
+    let mut tables = PieceSquareTables::default();
+
+    assert!(tables.parse_overrides(text).is_err());
+    assert_eq!(tables, PieceSquareTables::default());
+}
+
+#[test]
+fn test_load_tables() {
+    let path = std::env::temp_dir().join(format!("otus_pst_{}.txt", std::process::id()));
+    std::fs::write(&path, format!("pawn eg 200\n{}", "0 ".repeat(64))).unwrap();
+    let tables = PieceSquareTables::load(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    let tables = tables.unwrap();
+
+    assert_eq!(
+        tables.get(
+            Piece(PieceType::Pawn, Color::Black),
+            Square(File::A, Rank::_7)
+        ),
+        TaperedScore::new(47, 200)
+    );
+    assert!(PieceSquareTables::load("does/not/exist.txt").is_err());
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 w kq - 0 1")]
+fn test_trace_adds_up_to_eval(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let trace = eval_trace(&board);
+    let white_score = trace.total().interpolate(trace.phase);
+
+    assert_eq!(trace.score, smart_eval(&board));
+    assert_eq!(trace.phase, game_phase(&board));
+    match board.active_player {
+        Color::White => assert_eq!(trace.score.0, white_score),
+        Color::Black => assert_eq!(trace.score.0, -white_score),
+    }
+}
+
+#[rstest]
+#[case("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", 30)] // the rank counts, not only the file
+#[case("8/8/8/8/3k4/8/8/R3K3 w - - 0 1", 0)]
+#[case("8/8/2k5/8/8/8/8/R3K3 w - - 0 1", 10)]
+#[case("4k3/8/8/8/8/8/8/4K3 w - - 0 1", 0)] // nobody is ahead
+fn test_king_edge(#[case] fen: &str, #[case] expected: i32) {
+    let trace = eval_trace(&Board::from_fen(fen).unwrap());
+
+    assert_eq!(
+        trace.king_edge,
+        EvalTerm::new(TaperedScore::new(0, expected), TaperedScore::default())
+    );
+}
+
+#[test]
+fn test_trace_terms() {
+    // a knight against a rook that gives check
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2K2r w - - 0 1").unwrap();
+    let trace = eval_trace(&board);
+    let knight = PieceSquareTables::default_tables().material[PieceType::Knight as usize];
+    let rook = PieceSquareTables::default_tables().material[PieceType::Rook as usize];
+
+    assert_eq!(trace.material.total(), knight - rook);
+    assert_eq!(
+        trace.check,
+        EvalTerm::new(TaperedScore::new(-30, -30), TaperedScore::default())
+    );
+    let table = trace.to_string();
+    for (name, _) in trace.terms() {
+        assert!(table.contains(name));
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+
+use crate::board::models::Color;
+
+use super::{EvalBreakdown, EvalTerm, TaperedScore, MAX_PHASE};
+
+impl EvalTerm {
+    pub fn new(white: TaperedScore, black: TaperedScore) -> EvalTerm {
+        EvalTerm { white, black }
+    }
+
+    pub fn add(&mut self, color: Color, score: TaperedScore) {
+        match color {
+            Color::White => self.white += score,
+            Color::Black => self.black += score,
+        }
+    }
+
+    // white's view
+    pub fn total(self) -> TaperedScore {
+        self.white - self.black
+    }
+}
+
+impl EvalBreakdown {
+    pub fn terms(&self) -> [(&'static str, EvalTerm); 14] {
+        [
+            ("material", self.material),
+            ("piece-square", self.piece_squares),
+            ("pawn structure", self.pawn_structure),
+            ("passed pawns", self.passed_pawns),
+            ("pawn shield", self.pawn_shield),
+            ("king attack", self.king_attack),
+            ("mobility", self.mobility),
+            ("rook files", self.rook_files),
+            ("rook on 7th", self.rook_on_seventh),
+            ("bishop pair", self.bishop_pair),
+            ("outposts", self.outposts),
+            ("trapped", self.trapped),
+            ("king edge", self.king_edge),
+            ("check", self.check),
+        ]
+    }
+
+    // sum of all terms, white's view
+    pub fn total(&self) -> TaperedScore {
+        self.terms()
+            .iter()
+            .fold(TaperedScore::default(), |sum, (_, term)| sum + term.total())
+    }
+}
+
+/*
+The terms as a table in centipawns, followed by the phase and the score, e.g.
+           term |       white |       black |       total
+                |    mg    eg |    mg    eg |    mg    eg
+----------------+-------------+-------------+------------
+       material |  4039  3868 |  4039  3868 |     0     0
+*/
+impl fmt::Display for EvalBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>15} | {:>11} | {:>11} | {:>11}",
+            "term", "white", "black", "total"
+        )?;
+        writeln!(
+            f,
+            "{:>15} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+            "", "mg", "eg", "mg", "eg", "mg", "eg"
+        )?;
+        writeln!(f, "{:-<16}+{:-<13}+{:-<13}+{:-<12}", "", "", "", "")?;
+        for (name, term) in self.terms() {
+            let total = term.total();
+            writeln!(
+                f,
+                "{:>15} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}",
+                name,
+                term.white.mg,
+                term.white.eg,
+                term.black.mg,
+                term.black.eg,
+                total.mg,
+                total.eg
+            )?;
+        }
+        let total = self.total();
+        writeln!(f, "{:-<16}+{:-<13}+{:-<13}+{:-<12}", "", "", "", "")?;
+        writeln!(
+            f,
+            "{:>15} | {:>11} | {:>11} | {:>5} {:>5}",
+            "total", "", "", total.mg, total.eg
+        )?;
+        writeln!(
+            f,
+            "phase {}/{} ({} is the middlegame, 0 the endgame)",
+            self.phase, MAX_PHASE, MAX_PHASE
+        )?;
+        write!(
+            f,
+            "score {} cp for {} ({} cp for white)",
+            self.score.0,
+            self.active_player,
+            total.interpolate(self.phase)
+        )
+    }
+}
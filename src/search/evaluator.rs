@@ -0,0 +1,107 @@
+use crate::{
+    board::{models::LegalMove, Board},
+    hashing::{get_pawn_hash, update_pawn_hash},
+};
+
+use super::{
+    eval::{trace, PawnTable, PieceSquareTables, PAWN_TABLE_ENTRIES},
+    score::Score,
+};
+
+#[cfg(test)]
+mod tests;
+
+/*
+Static evaluation for the searches. Unlike a plain function, an evaluator can carry state:
+tables, caches, weights loaded from a file or accumulators that are updated move by move.
+The searches borrow it, so its state outlives a search, helper threads work on their own clone.
+Incremental hooks: the search calls reset with the root position, then make_move before it evaluates
+or searches the position after a move and unmake_move when it returns from it, in stack order.
+Both hooks get the board before the move. Evaluators without incremental state ignore them.
+*/
+pub trait Evaluator: Clone + Send {
+    // from the view of the side to move
+    fn evaluate(&mut self, board: &Board) -> Score;
+
+    fn reset(&mut self, _board: &Board) {}
+
+    fn make_move(&mut self, _board: &Board, _move: &LegalMove) {}
+
+    fn unmake_move(&mut self, _board: &Board, _move: &LegalMove) {}
+
+    fn make_null_move(&mut self, _board: &Board) {}
+
+    fn unmake_null_move(&mut self, _board: &Board) {}
+}
+
+// stateless evaluation functions such as eval::smart_eval and eval::get_material_eval
+impl<F> Evaluator for F
+where
+    F: Fn(&Board) -> Score + Clone + Send,
+{
+    fn evaluate(&mut self, board: &Board) -> Score {
+        self(board)
+    }
+}
+
+/*
+The evaluation of eval::smart_eval with its own piece-square tables and pawn table.
+The pawn table is kept from one search to the next, helper threads start with a copy.
+The pawn keys of all positions on the current search path are kept in a stack that follows the hooks,
+so the pawn table is probed without looking at the pawns. The root key stays on the stack after a search,
+so evaluate computes the key from scratch at the root, where it may be asked about any board.
+*/
+#[derive(Clone)]
+pub struct SmartEval {
+    tables: PieceSquareTables,
+    pawn_table: PawnTable,
+    pawn_keys: Vec<u64>,
+}
+
+impl Default for SmartEval {
+    fn default() -> Self {
+        SmartEval::new(PieceSquareTables::default())
+    }
+}
+
+impl SmartEval {
+    pub fn new(tables: PieceSquareTables) -> SmartEval {
+        SmartEval {
+            tables,
+            pawn_table: PawnTable::new(PAWN_TABLE_ENTRIES),
+            pawn_keys: Vec::new(),
+        }
+    }
+
+    pub fn tables(&self) -> &PieceSquareTables {
+        &self.tables
+    }
+}
+
+impl Evaluator for SmartEval {
+    fn evaluate(&mut self, board: &Board) -> Score {
+        let key = match self.pawn_keys[..] {
+            [_, .., key] => key,
+            _ => get_pawn_hash(board),
+        };
+        let pawns = self.pawn_table.probe(board, key);
+        trace(board, &self.tables, pawns).score
+    }
+
+    fn reset(&mut self, board: &Board) {
+        self.pawn_keys.clear();
+        self.pawn_keys.push(get_pawn_hash(board));
+    }
+
+    fn make_move(&mut self, board: &Board, move_: &LegalMove) {
+        if let Some(key) = self.pawn_keys.last() {
+            self.pawn_keys.push(update_pawn_hash(board, *key, move_));
+        }
+    }
+
+    fn unmake_move(&mut self, _board: &Board, _move: &LegalMove) {
+        if self.pawn_keys.len() > 1 {
+            self.pawn_keys.pop();
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::mpsc;
+
+use rstest::rstest;
+
+use crate::{
+    board::{models::LegalMove, Board},
+    game::PositionHistory,
+    hashing::{get_pawn_hash, TranspTable},
+    search::{
+        eval::smart_eval,
+        minimax::{search_minimax, search_minimax_cached, search_minimax_threaded_cached},
+        score::Score,
+        search_params::SearchParams,
+        time_management::SearchLimits,
+    },
+};
+
+use super::{Evaluator, SmartEval};
+
+// Replays the hooks on its own boards and checks that every evaluated board is the one they lead to
+#[derive(Clone, Default)]
+struct ReplayEval {
+    boards: Vec<Board>,
+}
+
+impl ReplayEval {
+    fn push(&mut self, board: &Board, apply: impl Fn(&mut Board)) {
+        assert_eq!(self.boards.last(), Some(board));
+        let mut new_board = *board;
+        apply(&mut new_board);
+        self.boards.push(new_board);
+    }
+
+    fn pop(&mut self, board: &Board) {
+        self.boards.pop();
+        assert_eq!(self.boards.last(), Some(board));
+    }
+}
+
+impl Evaluator for ReplayEval {
+    fn evaluate(&mut self, board: &Board) -> Score {
+        assert_eq!(self.boards.last(), Some(board));
+        smart_eval(board)
+    }
+
+    fn reset(&mut self, board: &Board) {
+        self.boards = vec![*board];
+    }
+
+    fn make_move(&mut self, board: &Board, move_: &LegalMove) {
+        self.push(board, |new_board| new_board.make_move(move_));
+    }
+
+    fn unmake_move(&mut self, board: &Board, _move: &LegalMove) {
+        self.pop(board);
+    }
+
+    fn make_null_move(&mut self, board: &Board) {
+        self.push(board, |new_board| new_board.make_null_move());
+    }
+
+    fn unmake_null_move(&mut self, board: &Board) {
+        self.pop(board);
+    }
+}
+
+#[rstest]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 w kq - 0 1")]
+fn test_hooks_follow_the_search(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let mut transp_table = TranspTable::new(1);
+    let history = PositionHistory::from_board(&board);
+
+    search_minimax_cached(
+        &board,
+        4,
+        ReplayEval::default(),
+        &mut transp_table,
+        &history,
+    );
+    search_minimax(&board, 2, ReplayEval::default());
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1")]
+fn test_smart_eval_evaluator(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(SmartEval::default().evaluate(&board), smart_eval(&board));
+    assert_eq!((smart_eval).evaluate(&board), smart_eval(&board));
+}
+
+// Checks that the pawn key of every evaluated board is the one computed from scratch
+#[derive(Clone, Default)]
+struct CheckedSmartEval(SmartEval);
+
+impl Evaluator for CheckedSmartEval {
+    fn evaluate(&mut self, board: &Board) -> Score {
+        assert_eq!(self.0.pawn_keys.last(), Some(&get_pawn_hash(board)));
+        let eval = self.0.evaluate(board);
+        assert_eq!(eval, smart_eval(board));
+        eval
+    }
+
+    fn reset(&mut self, board: &Board) {
+        self.0.reset(board);
+    }
+
+    fn make_move(&mut self, board: &Board, move_: &LegalMove) {
+        self.0.make_move(board, move_);
+    }
+
+    fn unmake_move(&mut self, board: &Board, move_: &LegalMove) {
+        self.0.unmake_move(board, move_);
+    }
+}
+
+#[rstest]
+// castling, en passant
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+// promotions with captures
+#[case("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1")]
+fn test_smart_eval_pawn_keys_follow_the_search(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let mut transp_table = TranspTable::new(1);
+
+    search_minimax_cached(
+        &board,
+        3,
+        CheckedSmartEval::default(),
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+    );
+}
+
+#[test]
+fn test_smart_eval_after_search() {
+    let board = Board::from_fen("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1").unwrap();
+    let other = Board::from_fen("4k3/pppp4/8/8/8/8/P1P1P1P1/4K3 w - - 0 1").unwrap();
+    let mut evaluator = SmartEval::default();
+    let (_stop_tx, stop_rx) = mpsc::channel();
+
+    search_minimax_threaded_cached(
+        &board,
+        &SearchLimits::from_depth(3),
+        1,
+        1,
+        SearchParams::default(),
+        &mut evaluator,
+        &mut TranspTable::new(1),
+        &PositionHistory::from_board(&board),
+        stop_rx,
+    );
+
+    assert_eq!(evaluator.evaluate(&board), smart_eval(&board));
+    assert_eq!(evaluator.evaluate(&other), smart_eval(&other));
+}
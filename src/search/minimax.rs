@@ -1,6 +1,6 @@
-use std::sync::mpsc;
+use std::{sync::mpsc, time::Duration};
 
-use rand::Rng;
+use rand::seq::SliceRandom;
 
 use crate::{
     board::{
@@ -8,207 +8,635 @@ use crate::{
         move_checking::{apply_legal_move, is_king_in_check},
         Board,
     },
-    hashing::{get_zobrist_hash, update_zobrist_hash, TranspEntry, TranspTable},
+    game::PositionHistory,
+    hashing::{
+        update_zobrist_hash, update_zobrist_hash_null, Bound, PackedMove, TranspEntry, TranspTable,
+    },
 };
 
-use super::eval::get_material_eval;
+mod quiescence;
+#[cfg(test)]
+mod tests;
 
-fn get_noise() -> f32 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(-0.1..0.1)
-}
+use super::{
+    eval::get_material_eval,
+    evaluator::Evaluator,
+    move_picker::{is_quiet, MoveKey, MovePicker, OrderingTables},
+    score::Score,
+    search_params::SearchParams,
+    time_management::{SearchLimits, TimeManager, MAX_DEPTH},
+};
+
+const MAX_PLY: usize = 2 * MAX_DEPTH as usize; // the quiescence search may go deeper than the iteration depth
+const CURRMOVE_DELAY: Duration = Duration::from_secs(1); // "currmove" is only reported in long searches
+
+// see SearchParams for what each part of the search does
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+const NULL_MOVE_REDUCTION: u8 = 2; // plus one ply per 6 plies of depth
+const REVERSE_FUTILITY_MAX_DEPTH: u8 = 3;
+const REVERSE_FUTILITY_MARGIN: i32 = 120; // per ply of depth
+const FUTILITY_MAX_DEPTH: u8 = 2;
+const FUTILITY_MARGIN: i32 = 150; // per ply of depth
+const LMR_MIN_DEPTH: u8 = 3;
+const LMR_FULL_DEPTH_MOVES: usize = 3; // moves searched without reduction
+const LMR_DEEP_REDUCTION_MOVES: usize = 8; // moves after which deep nodes are reduced by two plies
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+const ASPIRATION_WINDOW: i32 = 30; // initial half width, doubled after each failed search
+const ASPIRATION_MAX_WINDOW: i32 = 1000; // wider windows are replaced by an infinite one
 
-struct SearchResult {
-    eval: f32,
-    nodes_searched: u64,
+// random order, so that one of several moves of equal value is picked at random
+fn shuffled_legal_moves(board: &Board) -> Vec<LegalMove> {
+    let mut moves = board.get_legal_moves();
+    moves.shuffle(&mut rand::thread_rng());
+    moves
 }
 
-pub fn search_minimax_threaded_cached(
+// Searches until the limits are reached or "stop" is received on rx, then prints the best move
+// history: all positions of the game so far, the last one must be board
+// multi_pv: number of best moves reported after each iteration
+// threads: number of search threads, see search_iterative_deepening
+#[allow(clippy::too_many_arguments)]
+pub fn search_minimax_threaded_cached<E: Evaluator>(
     board: &Board,
-    depth: u8,
-    eval_fn: fn(&Board) -> f32,
+    limits: &SearchLimits,
+    multi_pv: usize,
+    threads: usize,
+    params: SearchParams,
+    evaluator: &mut E,
     trans_table: &mut TranspTable,
+    history: &PositionHistory,
     rx: mpsc::Receiver<()>,
 ) {
-    let moves = board.get_legal_moves(); // Assumption: this is never called in checkmated or stalemate position
-    let mut best_move = moves[0].clone();
-    let mut best_score = f32::MIN;
-    let initial_hash = get_zobrist_hash(board);
-    let mut nodes_searched = 0;
-    for move_ in moves {
-        let new_board = apply_legal_move(board, &move_);
-        let time = std::time::Instant::now();
-        let result = nega_max_cached(
-            &new_board,
-            depth - 1,
-            f32::MIN,
-            f32::MAX,
-            eval_fn,
-            trans_table,
-            update_zobrist_hash(board, initial_hash, &move_),
-        );
-        let time_elapsed = time.elapsed().as_micros();
-        nodes_searched += result.nodes_searched;
-        println!(
-            "info nodes {} nps {}",
-            nodes_searched,
-            (result.nodes_searched as f64 / time_elapsed as f64 * 1_000_000.0) as i64
-        );
-        let score = -result.eval + get_noise(); // add noise to shuffle moves of equal value
-        if score > best_score {
-            best_score = score;
-            best_move = move_;
-        }
-        if rx.try_recv().is_ok() {
-            break;
-        }
-    }
+    let mut time_manager = TimeManager::new(limits, board.active_player, Some(rx));
+    let best_move = search_iterative_deepening(
+        board,
+        multi_pv,
+        threads,
+        params,
+        evaluator,
+        trans_table,
+        history,
+        &mut time_manager,
+    );
+    time_manager.wait_for_stop();
     println!("bestmove {}", best_move.to_move(board).to_uci_string(board))
 }
 
-pub fn search_minimax_cached(
-    board: &Board,
-    depth: u8,
-    eval_fn: fn(&Board) -> f32,
-    trans_table: &mut TranspTable,
-) -> LegalMove {
-    let moves = board.get_legal_moves(); // Assumption: this is never called in checkmated or stalemate position
-    let mut best_move = moves[0].clone();
-    let mut best_score = f32::MIN;
-    let initial_hash = get_zobrist_hash(board);
-    for move_ in moves {
-        let new_board = apply_legal_move(board, &move_);
-        let result = nega_max_cached(
-            &new_board,
-            depth - 1,
-            f32::MIN,
-            f32::MAX,
-            eval_fn,
+// A root move with its eval and principal variation, the pv starts with the move itself
+struct RootMove {
+    move_: LegalMove,
+    eval: Score,
+    pv: Vec<LegalMove>,
+}
+
+// State shared by all nodes of one search
+struct CachedSearch<'a, E: Evaluator> {
+    evaluator: &'a mut E,
+    params: SearchParams,
+    trans_table: &'a TranspTable,
+    history: PositionHistory,
+    time_manager: &'a mut TimeManager,
+    pv_table: Vec<Vec<LegalMove>>, // triangular pv table, pv_table[ply] is the best line found from ply on
+    seldepth: usize,               // deepest ply reached in the current iteration
+    ordering: OrderingTables,
+    played_moves: Vec<Option<MoveKey>>, // played_moves[ply] led to the position at ply, None after a null move
+}
+
+impl<'a, E: Evaluator> CachedSearch<'a, E> {
+    fn new(
+        evaluator: &'a mut E,
+        params: SearchParams,
+        trans_table: &'a TranspTable,
+        history: &PositionHistory,
+        time_manager: &'a mut TimeManager,
+    ) -> CachedSearch<'a, E> {
+        CachedSearch {
+            evaluator,
+            params,
             trans_table,
-            update_zobrist_hash(board, initial_hash, &move_),
-        );
-        let score = -result.eval + get_noise(); // add noise to shuffle moves of equal value
-        if score > best_score {
-            best_score = score;
-            best_move = move_;
+            history: history.clone(),
+            time_manager,
+            pv_table: vec![Vec::new(); MAX_PLY],
+            seldepth: 0,
+            ordering: OrderingTables::new(MAX_PLY),
+            played_moves: vec![None; MAX_PLY + 1],
         }
     }
-    best_move
 }
 
-fn get_cached_eval(board: &Board, board_hash: u64, move_: &LegalMove, cache: &TranspTable) -> f32 {
-    let hash = update_zobrist_hash(board, board_hash, move_);
-    match cache.get(hash) {
-        Some(entry) => entry.value,
-        None => f32::MAX,
+fn pv_to_uci_string(board: &Board, pv: &[LegalMove]) -> String {
+    let mut board = *board;
+    let mut moves = Vec::with_capacity(pv.len());
+    for move_ in pv {
+        moves.push(move_.to_move(&board).to_uci_string(&board));
+        board.make_move(move_);
     }
+    moves.join(" ")
 }
 
-fn nega_max_cached(
-    board: &Board,
-    depth: u8,
-    mut alpha: f32,
-    beta: f32,
-    eval_fn: fn(&Board) -> f32,
-    trans_table: &mut TranspTable,
-    board_hash: u64,
-) -> SearchResult {
-    let cache_entry = trans_table.get(board_hash);
-    if let Some(entry) = cache_entry {
-        if entry.depth >= depth {
-            return SearchResult {
-                eval: entry.value,
-                nodes_searched: 0,
+impl<E: Evaluator> CachedSearch<'_, E> {
+    fn print_info(&self, depth: u8, multi_pv: Option<usize>, root_move: &RootMove, board: &Board) {
+        let elapsed = self.time_manager.elapsed();
+        let multi_pv = match multi_pv {
+            Some(rank) => format!(" multipv {}", rank),
+            None => String::new(),
+        };
+        println!(
+            "info depth {} seldepth {}{} score {} nodes {} time {} nps {} hashfull {} pv {}",
+            depth,
+            self.seldepth,
+            multi_pv,
+            root_move.eval,
+            self.time_manager.total_nodes(),
+            elapsed.as_millis(),
+            (self.time_manager.total_nodes() as f64 / elapsed.as_secs_f64().max(1e-6)) as u64,
+            self.trans_table.hashfull(),
+            pv_to_uci_string(board, &root_move.pv)
+        );
+    }
+
+    // Returns all searched moves, best first.
+    // Only the best multi_pv moves get exact evals, the others may be upper bounds.
+    // window: evals outside of it are bounds only, the search stops at the first move that reaches its upper end
+    // Returns None if the search was aborted before the first move was searched.
+    fn search_root(
+        &mut self,
+        board: &Board,
+        moves: &[LegalMove],
+        depth: u8,
+        multi_pv: usize,
+        report_currmove: bool,
+        window: (Score, Score),
+    ) -> Option<Vec<RootMove>> {
+        let (window_alpha, beta) = window;
+        let initial_hash = self.history.current();
+        let mut results = Vec::with_capacity(moves.len());
+        for (index, move_) in moves.iter().enumerate() {
+            if report_currmove && self.time_manager.elapsed() >= CURRMOVE_DELAY {
+                println!(
+                    "info depth {} currmove {} currmovenumber {}",
+                    depth,
+                    move_.to_move(board).to_uci_string(board),
+                    index + 1
+                );
+            }
+            let new_board = apply_legal_move(board, move_);
+            self.history
+                .push(update_zobrist_hash(board, initial_hash, move_));
+            self.played_moves[1] = Some(MoveKey::new(board, move_));
+            self.evaluator.make_move(board, move_);
+            // a move has to beat the multi_pv-th best move so far to be reported
+            let alpha = if results.len() >= multi_pv {
+                let mut evals: Vec<Score> = results.iter().map(|r: &RootMove| r.eval).collect();
+                evals.sort_unstable_by_key(|eval| -*eval);
+                evals[multi_pv - 1].max(window_alpha)
+            } else {
+                window_alpha
             };
+            let eval = -self.nega_max_cached(&new_board, depth - 1, 1, -beta, -alpha);
+            self.evaluator.unmake_move(board, move_);
+            self.history.pop();
+            if self.time_manager.is_stopped() {
+                // the eval of the interrupted move is meaningless, but all moves before it were fully searched
+                break;
+            }
+            let mut pv = vec![move_.clone()];
+            pv.extend_from_slice(&self.pv_table[1]);
+            results.push(RootMove {
+                move_: move_.clone(),
+                eval,
+                pv,
+            });
+            if eval >= beta {
+                break; // fail high, the window has to be widened anyway
+            }
         }
+        if results.is_empty() {
+            return None;
+        }
+        results.sort_by_key(|root_move| -root_move.eval); // stable, moves of equal value keep their order
+        Some(results)
     }
-    if depth == 0 {
-        let eval = match board.get_gamestate() {
-            GameState::Mated(_) => f32::MIN,
-            GameState::Stalemate => 0.0,
-            GameState::InProgress => eval_fn(board),
-        };
-        trans_table.put(
-            board_hash,
-            TranspEntry {
-                depth: 0,
-                value: eval,
-            },
-        ); // TODO experiment if this is actually faster
-        return SearchResult {
-            eval,
-            nodes_searched: 1,
+
+    /*
+    Aspiration windows: the root is searched with a narrow window around previous, the eval of the last iteration.
+    Most iterations end with an eval close to the previous one, and the narrow window allows more cutoffs.
+    If the best eval lies outside the window, the side it failed on is widened and the root is searched again.
+    The move that failed high is moved to the front of moves, the returned results are for a prefix of moves.
+    */
+    fn search_root_aspiration(
+        &mut self,
+        board: &Board,
+        moves: &mut Vec<LegalMove>,
+        depth: u8,
+        multi_pv: usize,
+        report_currmove: bool,
+        previous: Option<Score>,
+    ) -> Option<Vec<RootMove>> {
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha, mut beta) = match previous {
+            Some(previous)
+                if self.params.aspiration_windows
+                    && multi_pv == 1
+                    && depth >= ASPIRATION_MIN_DEPTH
+                    && !previous.is_mate() =>
+            {
+                (previous - Score(delta), previous + Score(delta))
+            }
+            _ => (-Score::INFINITY, Score::INFINITY),
         };
+        loop {
+            let results = self.search_root(
+                board,
+                moves,
+                depth,
+                multi_pv,
+                report_currmove,
+                (alpha, beta),
+            )?;
+            let best_eval = results[0].eval;
+            if self.time_manager.is_stopped() {
+                return Some(results);
+            }
+            delta *= 2;
+            if best_eval <= alpha && alpha > -Score::INFINITY {
+                alpha = if delta > ASPIRATION_MAX_WINDOW {
+                    -Score::INFINITY
+                } else {
+                    alpha - Score(delta)
+                };
+            } else if best_eval >= beta && beta < Score::INFINITY {
+                beta = if delta > ASPIRATION_MAX_WINDOW {
+                    Score::INFINITY
+                } else {
+                    beta + Score(delta)
+                };
+                *moves = best_first(&results, moves);
+            } else {
+                return Some(results);
+            }
+        }
     }
-    let mut moves = board.get_legal_moves(); // Avoid calling get_gamestate because it would duplicate work from get_legal_moves()
-    if moves.is_empty() {
-        let eval = if is_king_in_check(board) {
-            f32::MIN // mated
+
+    // move_ is the new best move at ply, followed by the best line found after it
+    fn update_pv(&mut self, ply: usize, move_: LegalMove) {
+        let (parent, child) = self.pv_table.split_at_mut(ply + 1);
+        parent[ply].clear();
+        parent[ply].push(move_);
+        parent[ply].extend_from_slice(&child[0]);
+    }
+
+    // history: hashes of all positions leading to board, the last one is the hash of board
+    // ply: distance to the root, the best line from board is left in pv_table[ply]
+    // Once the time manager stops the search, the returned eval is meaningless and nothing is cached
+    fn nega_max_cached(
+        &mut self,
+        board: &Board,
+        depth: u8,
+        ply: usize,
+        mut alpha: Score,
+        beta: Score,
+    ) -> Score {
+        // must be checked before the cache lookup, cached values do not know the path to the position
+        if self.history.is_repetition(board.halfmove_clock) {
+            self.pv_table[ply].clear();
+            return Score::ZERO;
+        }
+        let in_check = is_king_in_check(board);
+        // extended before the horizon, so that checks are not left to the quiescence search
+        let depth =
+            if in_check && self.params.check_extensions && ply + (depth as usize) < MAX_PLY - 1 {
+                depth + 1
+            } else {
+                depth
+            };
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+        self.pv_table[ply].clear();
+        self.seldepth = self.seldepth.max(ply);
+        if self.time_manager.visit_node() {
+            return Score::ZERO;
+        }
+        let board_hash = self.history.current();
+        let alpha_orig = alpha;
+        let mut tt_move = None;
+        if let Some(entry) = self.trans_table.get(board_hash) {
+            tt_move = entry.best_move;
+            if entry.depth >= depth {
+                let value = entry.value.from_tt(ply);
+                match entry.bound {
+                    Bound::Exact => return value,
+                    Bound::Lower if value >= beta => return value,
+                    Bound::Upper if value <= alpha => return value,
+                    _ => (),
+                }
+            }
+        }
+        if board.is_insufficient_material() {
+            return Score::ZERO;
+        }
+        let previous = self.played_moves[ply];
+        let static_eval = if in_check {
+            None
         } else {
-            0.0 // stalemate
+            Some(self.evaluator.evaluate(board))
         };
-        trans_table.put(
-            board_hash,
-            TranspEntry {
-                depth: 0,
-                value: eval,
-            },
-        ); // TODO experiment if this is actually faster
-        return SearchResult {
-            eval,
-            nodes_searched: 1,
+        if let Some(static_eval) = static_eval {
+            if self.params.reverse_futility
+                && depth <= REVERSE_FUTILITY_MAX_DEPTH
+                && !beta.is_mate()
+                && static_eval - Score(REVERSE_FUTILITY_MARGIN * depth as i32) >= beta
+            {
+                return beta;
+            }
+            if self.params.null_move
+                && depth >= NULL_MOVE_MIN_DEPTH
+                && static_eval >= beta
+                && !beta.is_mate()
+                && previous.is_some() // no two null moves in a row
+                && board.has_non_pawn_material(board.active_player)
+            {
+                let score = self.null_move_search(board, depth, ply, beta);
+                if self.time_manager.is_stopped() {
+                    return alpha;
+                }
+                if score >= beta {
+                    return beta;
+                }
+            }
+        }
+        // quiet moves cannot raise alpha if even a large positional gain would not bring the eval up to alpha
+        let futile = self.params.futility
+            && depth <= FUTILITY_MAX_DEPTH
+            && !alpha.is_mate()
+            && static_eval
+                .is_some_and(|eval| eval + Score(FUTILITY_MARGIN * depth as i32) <= alpha);
+        let mut picker = MovePicker::new(tt_move, ply, previous, &self.ordering);
+        let mut searched_quiets = Vec::new();
+        let mut has_legal_moves = false;
+        let mut moves_searched = 0;
+        let mut best_move = None;
+        while let Some(move_) = picker.next(board, &self.ordering) {
+            if !has_legal_moves && board.is_fifty_move_draw() {
+                return Score::ZERO; // checkmate takes precedence, so only after a legal move was found
+            }
+            has_legal_moves = true;
+            let new_board = apply_legal_move(board, &move_);
+            let quiet = is_quiet(&move_);
+            let gives_check = is_king_in_check(&new_board);
+            if futile && moves_searched > 0 && quiet && !gives_check {
+                continue;
+            }
+            self.history
+                .push(update_zobrist_hash(board, board_hash, &move_));
+            self.played_moves[ply + 1] = Some(MoveKey::new(board, &move_));
+            self.evaluator.make_move(board, &move_);
+            // late quiet moves rarely raise alpha, a reduced null window search has to show that they do
+            let reduction = if self.params.late_move_reductions
+                && depth >= LMR_MIN_DEPTH
+                && moves_searched >= LMR_FULL_DEPTH_MOVES
+                && quiet
+                && !in_check
+                && !gives_check
+            {
+                1 + u8::from(moves_searched >= LMR_DEEP_REDUCTION_MOVES && depth >= 6)
+            } else {
+                0
+            };
+            let mut score = alpha + Score(1);
+            if reduction > 0 {
+                score = -self.nega_max_cached(
+                    &new_board,
+                    depth - 1 - reduction,
+                    ply + 1,
+                    -alpha - Score(1),
+                    -alpha,
+                );
+            }
+            if score > alpha && !self.time_manager.is_stopped() {
+                score = -self.nega_max_cached(&new_board, depth - 1, ply + 1, -beta, -alpha);
+            }
+            self.evaluator.unmake_move(board, &move_);
+            self.history.pop();
+            moves_searched += 1;
+            if self.time_manager.is_stopped() {
+                return alpha;
+            }
+            if score >= beta {
+                self.ordering
+                    .update_cutoff(board, &move_, ply, depth, previous, &searched_quiets);
+                let packed = PackedMove::from_legal_move(&move_, board);
+                self.trans_table.put(
+                    board_hash,
+                    TranspEntry::new(depth, beta.to_tt(ply), Bound::Lower, Some(packed)),
+                );
+                return beta;
+            }
+            if quiet {
+                searched_quiets.push(move_.clone());
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(PackedMove::from_legal_move(&move_, board));
+                self.update_pv(ply, move_);
+            }
+        }
+        if !has_legal_moves {
+            let eval = if is_king_in_check(board) {
+                Score::mated_in(ply)
+            } else {
+                Score::ZERO // stalemate
+            };
+            self.trans_table.put(
+                board_hash,
+                TranspEntry::new(0, eval.to_tt(ply), Bound::Exact, None),
+            ); // TODO experiment if this is actually faster
+            return eval;
+        }
+        // without a move that raised alpha, all moves failed low and alpha is only an upper bound
+        let bound = if alpha > alpha_orig {
+            Bound::Exact
+        } else {
+            Bound::Upper
         };
+        self.trans_table.put(
+            board_hash,
+            TranspEntry::new(depth, alpha.to_tt(ply), bound, best_move),
+        );
+        alpha
     }
-    // move ordering
-    moves.sort_unstable_by(|a, b| {
-        let eval_a = get_cached_eval(board, board_hash, a, trans_table);
-        let eval_b = get_cached_eval(board, board_hash, b, trans_table);
-        eval_a.partial_cmp(&eval_b).unwrap() // want to sort valuations in ascending order, these are opponent evals, opps worst situation is my best move
-    });
-    let mut nodes_searched = 0;
-    for move_ in moves {
-        let new_board = apply_legal_move(board, &move_);
-        let result = nega_max_cached(
-            &new_board,
-            depth - 1,
+
+    // The value of passing the turn, searched with a null window around beta and reduced depth.
+    // If it still fails high, any real move is very likely to fail high as well.
+    fn null_move_search(&mut self, board: &Board, depth: u8, ply: usize, beta: Score) -> Score {
+        let mut null_board = *board;
+        null_board.make_null_move();
+        self.history
+            .push(update_zobrist_hash_null(board, self.history.current()));
+        self.played_moves[ply + 1] = None;
+        self.evaluator.make_null_move(board);
+        let reduction = NULL_MOVE_REDUCTION + depth / 6;
+        let score = -self.nega_max_cached(
+            &null_board,
+            depth.saturating_sub(1 + reduction),
+            ply + 1,
             -beta,
-            -alpha,
-            eval_fn,
+            -beta + Score(1),
+        );
+        self.evaluator.unmake_null_move(board);
+        self.history.pop();
+        score
+    }
+}
+
+// moves with those of results first, in the order of results. results are for a prefix of moves.
+fn best_first(results: &[RootMove], moves: &[LegalMove]) -> Vec<LegalMove> {
+    let mut ordered: Vec<LegalMove> = results.iter().map(|r| r.move_.clone()).collect();
+    ordered.extend_from_slice(&moves[results.len()..]);
+    ordered
+}
+
+// Searches with increasing depth until the time manager stops it.
+// The best move of the previous iteration is searched first, so an aborted iteration can still improve on it.
+/*
+Lazy SMP: threads - 1 helper threads search the same position without reporting anything.
+They share the transposition table with the main thread and fill it with results the main thread would need later.
+Half of them start one ply deeper, so that the threads get out of step and search different parts of the tree.
+Only the main thread decides the move, the helpers are stopped as soon as it is done.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn search_iterative_deepening<E: Evaluator>(
+    board: &Board,
+    multi_pv: usize,
+    threads: usize,
+    params: SearchParams,
+    evaluator: &mut E,
+    trans_table: &mut TranspTable,
+    history: &PositionHistory,
+    time_manager: &mut TimeManager,
+) -> LegalMove {
+    trans_table.new_search();
+    let trans_table = &*trans_table;
+    std::thread::scope(|scope| {
+        for thread_id in 1..threads {
+            let mut helper_time_manager = time_manager.helper();
+            let mut evaluator = evaluator.clone();
+            scope.spawn(move || {
+                let start_depth = 1 + (thread_id % 2) as u8;
+                iterative_deepening(
+                    board,
+                    1,
+                    params,
+                    &mut evaluator,
+                    trans_table,
+                    history,
+                    &mut helper_time_manager,
+                    start_depth,
+                    false,
+                );
+            });
+        }
+        let best_move = iterative_deepening(
+            board,
+            multi_pv,
+            params,
+            evaluator,
             trans_table,
-            update_zobrist_hash(board, board_hash, &move_),
+            history,
+            time_manager,
+            1,
+            true,
         );
-        let score = -result.eval;
-        if score >= beta {
-            return SearchResult {
-                eval: beta,
-                nodes_searched,
-            };
+        time_manager.stop_helpers();
+        best_move
+    })
+}
+
+// main_thread: reports the search progress, always completes the first iteration
+#[allow(clippy::too_many_arguments)]
+fn iterative_deepening<E: Evaluator>(
+    board: &Board,
+    multi_pv: usize,
+    params: SearchParams,
+    evaluator: &mut E,
+    trans_table: &TranspTable,
+    history: &PositionHistory,
+    time_manager: &mut TimeManager,
+    start_depth: u8,
+    main_thread: bool,
+) -> LegalMove {
+    let mut moves = shuffled_legal_moves(board); // Assumption: this is never called in checkmated or stalemate position
+    evaluator.reset(board);
+    let mut search = CachedSearch::new(evaluator, params, trans_table, history, time_manager);
+    let mut depth = start_depth;
+    let mut previous_eval = None;
+    // the first iteration is always started, otherwise there would be no sensible move
+    while search.time_manager.can_start_iteration(depth) || (main_thread && depth == 1) {
+        search.seldepth = 0;
+        let Some(results) = search.search_root_aspiration(
+            board,
+            &mut moves,
+            depth,
+            multi_pv,
+            main_thread,
+            previous_eval,
+        ) else {
+            break;
+        };
+        if main_thread {
+            for (rank, root_move) in results.iter().take(multi_pv).enumerate() {
+                let rank = if multi_pv > 1 { Some(rank + 1) } else { None };
+                search.print_info(depth, rank, root_move, board);
+            }
         }
-        nodes_searched += result.nodes_searched;
-        if score > alpha {
-            alpha = score;
+        // best moves first in the next iteration, the searched moves are a prefix of moves
+        let best_eval = results[0].eval;
+        moves = best_first(&results, &moves);
+        previous_eval = Some(best_eval);
+        if best_eval.is_mate() && best_eval > Score::ZERO {
+            break; // forced mate found, deeper searches can only find a shorter one
         }
+        depth += 1;
     }
-    trans_table.put(
-        board_hash,
-        TranspEntry {
-            depth,
-            value: alpha,
-        },
+    moves[0].clone()
+}
+
+pub fn search_minimax_cached<E: Evaluator>(
+    board: &Board,
+    depth: u8,
+    mut evaluator: E,
+    trans_table: &mut TranspTable,
+    history: &PositionHistory,
+) -> LegalMove {
+    let moves = shuffled_legal_moves(board); // Assumption: this is never called in checkmated or stalemate position
+    trans_table.new_search();
+    let mut time_manager = TimeManager::unlimited();
+    evaluator.reset(board);
+    let mut search = CachedSearch::new(
+        &mut evaluator,
+        SearchParams::default(),
+        trans_table,
+        history,
+        &mut time_manager,
     );
-    SearchResult {
-        eval: alpha,
-        nodes_searched,
-    }
+    let window = (-Score::INFINITY, Score::INFINITY);
+    let results = search
+        .search_root(board, &moves, depth, 1, false, window)
+        .unwrap();
+    results[0].move_.clone()
 }
 
-pub fn search_minimax(board: &Board, depth: u32, eval_fn: fn(&Board) -> f32) -> LegalMove {
-    let moves = board.get_legal_moves(); // Assumption: this is never called in checkmated or stalemate position
+pub fn search_minimax<E: Evaluator>(board: &Board, depth: u32, mut evaluator: E) -> LegalMove {
+    evaluator.reset(board);
+    let moves = shuffled_legal_moves(board); // Assumption: this is never called in checkmated or stalemate position
     let mut best_move = moves[0].clone();
-    let mut best_score = f32::MIN;
+    let mut best_score = -Score::INFINITY;
     for move_ in moves {
         let new_board = apply_legal_move(board, &move_);
-        let score = -nega_max(&new_board, depth - 1, eval_fn) + get_noise(); // add noise to shuffle moves of equal value
+        evaluator.make_move(board, &move_);
+        let score = -nega_max(&new_board, depth - 1, &mut evaluator);
+        evaluator.unmake_move(board, &move_);
         if score > best_score {
             best_score = score;
             best_move = move_;
@@ -217,18 +645,21 @@ pub fn search_minimax(board: &Board, depth: u32, eval_fn: fn(&Board) -> f32) ->
     best_move
 }
 
-pub fn search_minimax_threaded(
+pub fn search_minimax_threaded<E: Evaluator>(
     board: &Board,
     depth: u32,
-    eval_fn: fn(&Board) -> f32,
+    mut evaluator: E,
     rx: mpsc::Receiver<()>,
 ) {
-    let moves = board.get_legal_moves(); // Assumption: this is never called in checkmated or stalemate position
+    evaluator.reset(board);
+    let moves = shuffled_legal_moves(board); // Assumption: this is never called in checkmated or stalemate position
     let mut best_move = moves[0].clone();
-    let mut best_score = f32::MIN;
+    let mut best_score = -Score::INFINITY;
     for move_ in moves {
         let new_board = apply_legal_move(board, &move_);
-        let score = -nega_max(&new_board, depth - 1, eval_fn) + get_noise(); // add noise to shuffle moves of equal value
+        evaluator.make_move(board, &move_);
+        let score = -nega_max(&new_board, depth - 1, &mut evaluator);
+        evaluator.unmake_move(board, &move_);
         if score > best_score {
             best_score = score;
             best_move = move_;
@@ -240,26 +671,28 @@ pub fn search_minimax_threaded(
     println!("bestmove {}", best_move.to_move(board).to_uci_string(board))
 }
 
-fn nega_max(board: &Board, depth: u32, eval_fn: fn(&Board) -> f32) -> f32 {
+fn nega_max<E: Evaluator>(board: &Board, depth: u32, evaluator: &mut E) -> Score {
     if depth == 0 {
         match board.get_gamestate() {
-            GameState::Mated(_) => return f32::MIN,
-            GameState::Stalemate => return 0.0,
-            GameState::InProgress => return eval_fn(board),
+            GameState::Mated(_) => return -Score::MATE,
+            GameState::Stalemate | GameState::Draw(_) => return Score::ZERO,
+            GameState::InProgress => return evaluator.evaluate(board),
         }
     }
     let moves = board.get_legal_moves(); // Avoid calling get_gamestate because it would duplicate work from get_legal_moves()
     if moves.is_empty() {
         if is_king_in_check(board) {
-            return f32::MIN; // mated
+            return -Score::MATE; // mated
         } else {
-            return 0.0; // stalemate
+            return Score::ZERO; // stalemate
         }
     }
-    let mut best_score = f32::MIN; // if no legal moves, return worst possible score TODO fix this for stalemate
+    let mut best_score = -Score::INFINITY;
     for move_ in moves {
         let new_board = apply_legal_move(board, &move_);
-        let score = -nega_max(&new_board, depth - 1, eval_fn);
+        evaluator.make_move(board, &move_);
+        let score = -nega_max(&new_board, depth - 1, evaluator);
+        evaluator.unmake_move(board, &move_);
         if score > best_score {
             best_score = score;
         }
@@ -268,12 +701,20 @@ fn nega_max(board: &Board, depth: u32, eval_fn: fn(&Board) -> f32) -> f32 {
 }
 
 pub fn search_alpha_beta(board: &Board, depth: u32) -> LegalMove {
-    let moves = board.get_legal_moves(); // Assumption: this is never called in checkmated or stalemate position
+    let moves = shuffled_legal_moves(board); // Assumption: this is never called in checkmated or stalemate position
+    let mut ordering = OrderingTables::new(depth as usize);
     let mut best_move = moves[0].clone();
-    let mut best_score = f32::MIN;
+    let mut best_score = -Score::INFINITY;
     for move_ in moves {
         let new_board = apply_legal_move(board, &move_);
-        let score = alpha_beta_min_rec(&new_board, depth - 1, best_score, f32::MAX) + get_noise(); // add noise to shuffle moves of equal value
+        let score = -alpha_beta(
+            &new_board,
+            depth - 1,
+            1,
+            -Score::INFINITY,
+            -best_score,
+            &mut ordering,
+        );
         if score > best_score {
             best_score = score;
             best_move = move_;
@@ -282,55 +723,40 @@ pub fn search_alpha_beta(board: &Board, depth: u32) -> LegalMove {
     best_move
 }
 
-// alpha= minimum guaranteed score for me
-// beta= maximum guaranteed score for opponent
-fn alpha_beta_max_rec(board: &Board, depth: u32, mut alpha: f32, beta: f32) -> f32 {
+// Fail-hard negamax with material evaluation, from the view of the side to move.
+// alpha: score the side to move is already guaranteed, beta: score the opponent is already guaranteed
+fn alpha_beta(
+    board: &Board,
+    depth: u32,
+    ply: usize,
+    mut alpha: Score,
+    beta: Score,
+    ordering: &mut OrderingTables,
+) -> Score {
     let gamestate = board.get_gamestate();
     if gamestate == GameState::Mated(board.active_player) {
-        return f32::MIN;
+        return -Score::MATE;
     }
-    if gamestate == GameState::Stalemate {
-        return 0.0;
+    if matches!(gamestate, GameState::Stalemate | GameState::Draw(_)) {
+        return Score::ZERO;
     }
     if depth == 0 {
         return get_material_eval(board);
     }
-    let moves = board.get_legal_moves();
-    for move_ in moves {
+    let mut picker = MovePicker::new(None, ply, None, ordering);
+    let mut searched_quiets = Vec::new();
+    while let Some(move_) = picker.next(board, ordering) {
         let new_board = apply_legal_move(board, &move_);
-        let score = alpha_beta_min_rec(&new_board, depth - 1, alpha, beta);
+        let score = -alpha_beta(&new_board, depth - 1, ply + 1, -beta, -alpha, ordering);
         if score >= beta {
+            ordering.update_cutoff(board, &move_, ply, depth as u8, None, &searched_quiets);
             return beta;
         }
         if score > alpha {
             alpha = score;
         }
-    }
-    alpha
-}
-
-// alpha= minimum guaranteed score for opponent
-// beta= maximum guaranteed score for me
-fn alpha_beta_min_rec(board: &Board, depth: u32, alpha: f32, mut beta: f32) -> f32 {
-    let gamestate = board.get_gamestate();
-    if gamestate == GameState::Mated(board.active_player) {
-        return f32::MIN;
-    }
-    if gamestate == GameState::Stalemate {
-        return 0.0;
-    }
-    if depth == 0 {
-        return get_material_eval(board);
-    }
-    let moves = board.get_legal_moves();
-    for move_ in moves {
-        let new_board = apply_legal_move(board, &move_);
-        let score = -alpha_beta_max_rec(&new_board, depth - 1, alpha, beta);
-        if score <= alpha {
-            return alpha;
-        }
-        if score < beta {
-            beta = score;
+        if is_quiet(&move_) {
+            searched_quiets.push(move_);
         }
     }
     alpha
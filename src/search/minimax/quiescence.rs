@@ -0,0 +1,87 @@
+use crate::board::{
+    move_checking::{apply_legal_move, is_king_in_check},
+    Board,
+};
+
+use super::{
+    super::{
+        evaluator::Evaluator,
+        move_picker::{material_gain, mvv_lva},
+        score::Score,
+    },
+    CachedSearch, MAX_PLY,
+};
+
+const DELTA_MARGIN: i32 = 200; // positional gain a capture may bring on top of the captured material
+
+impl<E: Evaluator> CachedSearch<'_, E> {
+    /*
+    Searches captures until the position is quiet, so that the eval is never taken in the middle of an exchange.
+    The side to move may decline all captures (stand pat), the static eval is then a lower bound.
+    Captures that cannot raise alpha even with the captured piece as a gift are skipped (delta pruning),
+    as well as captures that lose material in the static exchange evaluation.
+    In check there is no stand pat, all evasions are searched, so that mates are still found.
+    */
+    pub(super) fn quiescence(
+        &mut self,
+        board: &Board,
+        ply: usize,
+        mut alpha: Score,
+        beta: Score,
+    ) -> Score {
+        self.pv_table[ply].clear();
+        self.seldepth = self.seldepth.max(ply);
+        if self.time_manager.visit_node() {
+            return Score::ZERO;
+        }
+        if ply >= MAX_PLY - 1 {
+            return self.evaluator.evaluate(board);
+        }
+        let in_check = is_king_in_check(board);
+        let stand_pat = if in_check {
+            None
+        } else {
+            Some(self.evaluator.evaluate(board))
+        };
+        let mut moves = match stand_pat {
+            Some(stand_pat) => {
+                if stand_pat >= beta {
+                    return beta;
+                }
+                if stand_pat > alpha {
+                    alpha = stand_pat;
+                }
+                board.get_capture_moves()
+            }
+            None => board.get_legal_moves(),
+        };
+        if moves.is_empty() && in_check {
+            return Score::mated_in(ply);
+        }
+        moves.sort_by_cached_key(|move_| -mvv_lva(board, move_));
+        for move_ in moves {
+            if let Some(stand_pat) = stand_pat {
+                if stand_pat + Score(material_gain(&move_) + DELTA_MARGIN) <= alpha
+                    || board.see(&move_) < 0
+                {
+                    continue;
+                }
+            }
+            let new_board = apply_legal_move(board, &move_);
+            self.evaluator.make_move(board, &move_);
+            let score = -self.quiescence(&new_board, ply + 1, -beta, -alpha);
+            self.evaluator.unmake_move(board, &move_);
+            if self.time_manager.is_stopped() {
+                return alpha;
+            }
+            if score >= beta {
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, move_);
+            }
+        }
+        alpha
+    }
+}
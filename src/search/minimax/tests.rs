@@ -0,0 +1,283 @@
+use rstest::rstest;
+
+use crate::{
+    board::{models::LegalMove, Board},
+    game::PositionHistory,
+    hashing::TranspTable,
+    search::{
+        eval::{get_material_eval, smart_eval},
+        evaluator::SmartEval,
+        move_picker::OrderingTables,
+        score::Score,
+        search_params::SearchParams,
+        time_management::{SearchLimits, TimeManager},
+    },
+};
+
+use super::{alpha_beta, nega_max, pv_to_uci_string, search_alpha_beta, CachedSearch};
+
+fn search_pv(fen: &str, depth: u8) -> (Board, Score, Vec<LegalMove>) {
+    let board = Board::from_fen(fen).unwrap();
+    let transp_table = TranspTable::new(1);
+    let mut time_manager =
+        TimeManager::new(&SearchLimits::from_depth(depth), board.active_player, None);
+    let mut evaluator = SmartEval::default();
+    let mut search = CachedSearch::new(
+        &mut evaluator,
+        SearchParams::default(),
+        &transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+    let moves = board.get_legal_moves();
+    let best = search
+        .search_root(
+            &board,
+            &moves,
+            depth,
+            1,
+            false,
+            (-Score::INFINITY, Score::INFINITY),
+        )
+        .unwrap()
+        .remove(0);
+    (board, best.eval, best.pv)
+}
+
+#[test]
+fn test_pv_of_mate_in_two() {
+    // 1. Rd8+ Rxd8 2. Rxd8#
+    let (board, eval, pv) = search_pv("r5k1/5ppp/8/8/8/3R4/5PPP/3R2K1 w - - 0 1", 4);
+
+    assert_eq!(eval, Score::mate_in(3));
+    assert_eq!(pv_to_uci_string(&board, &pv), "d3d8 a8d8 d1d8");
+    assert_eq!(eval.to_uci_string(), "mate 2");
+}
+
+#[rstest]
+#[case(1)]
+#[case(2)]
+#[case(3)]
+#[case(4)]
+fn test_knight_stays_out_of_the_corner(#[case] depth: u8) {
+    // Nb6-a8 was once played here, leading to N7/7p/4k1p1/p3pp2/1b4Pr/5P2/6KP/1R6 b - - 1 37
+    let (board, _, pv) = search_pv("8/7p/1N2k1p1/p3pp2/1b4Pr/5P2/6KP/1R6 w - - 0 37", depth);
+
+    assert_ne!(pv_to_uci_string(&board, &pv[..1]), "b6a8");
+}
+
+#[test]
+fn test_pv_is_legal_line() {
+    let (board, _, pv) = search_pv(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+    );
+
+    assert!(!pv.is_empty());
+    let mut board = board;
+    for move_ in &pv {
+        assert!(board.get_legal_moves().contains(move_));
+        board.make_move(move_);
+    }
+}
+
+#[test]
+fn test_prefers_shorter_mate() {
+    // Qg7# mates at once, Qh8+?? only delays it
+    let (board, eval, pv) = search_pv("7k/8/5KQ1/8/8/8/8/8 w - - 0 1", 4);
+
+    assert_eq!(eval, Score::mate_in(1));
+    assert_eq!(pv[0].to_san(&board), "Qg7#");
+}
+
+#[test]
+fn test_mated_score_counts_plies() {
+    // the only move Kg8 runs into Ra8#
+    let (_, eval, pv) = search_pv("7k/8/6K1/8/8/8/8/R7 b - - 0 1", 3);
+
+    assert_eq!(eval, Score::mated_in(2));
+    assert_eq!(pv.len(), 2);
+}
+
+fn new_search<'a>(
+    evaluator: &'a mut SmartEval,
+    transp_table: &'a TranspTable,
+    time_manager: &'a mut TimeManager,
+    board: &Board,
+) -> CachedSearch<'a, SmartEval> {
+    CachedSearch::new(
+        evaluator,
+        SearchParams::default(),
+        transp_table,
+        &PositionHistory::from_board(board),
+        time_manager,
+    )
+}
+
+#[test]
+fn test_bounds_from_narrow_windows_do_not_change_value() {
+    // fail-high and fail-low entries of null window searches must not be mistaken for exact values
+    let board = Board::from_fen("4k3/2p5/3p4/8/2N1B3/8/4P3/4K3 w - - 0 1").unwrap();
+    let depth = 3;
+    let expected = {
+        let mut evaluator = SmartEval::default();
+        let transp_table = TranspTable::new(1);
+        let mut time_manager = TimeManager::unlimited();
+        let mut search = new_search(&mut evaluator, &transp_table, &mut time_manager, &board);
+        search.nega_max_cached(&board, depth, 0, -Score::INFINITY, Score::INFINITY)
+    };
+    let mut evaluator = SmartEval::default();
+    let transp_table = TranspTable::new(1);
+    let mut time_manager = TimeManager::unlimited();
+    let mut search = new_search(&mut evaluator, &transp_table, &mut time_manager, &board);
+    for guess in [-500, -100, 0, 100, 300, 500, 1000] {
+        search.nega_max_cached(&board, depth, 0, Score(guess), Score(guess + 1));
+    }
+    let value = search.nega_max_cached(&board, depth, 0, -Score::INFINITY, Score::INFINITY);
+
+    assert_eq!(value, expected);
+}
+
+#[rstest]
+// the knight is defended by a pawn, Rxd6 cxd6 loses material
+#[case("4k3/2p5/3n4/8/8/8/8/3RK3 w - - 0 1", false)]
+// the pawn is free
+#[case("4k3/8/3p4/8/8/8/8/3RK3 w - - 0 1", true)]
+fn test_quiescence_resolves_captures(#[case] fen: &str, #[case] wins_material: bool) {
+    let board = Board::from_fen(fen).unwrap();
+    let mut evaluator = SmartEval::default();
+    let transp_table = TranspTable::new(1);
+    let mut time_manager = TimeManager::unlimited();
+    let mut search = new_search(&mut evaluator, &transp_table, &mut time_manager, &board);
+    let value = search.quiescence(&board, 0, -Score::INFINITY, Score::INFINITY);
+
+    let stand_pat = smart_eval(&board);
+    if wins_material {
+        assert!(value > stand_pat);
+        assert_eq!(pv_to_uci_string(&board, &search.pv_table[0]), "d1d6");
+    } else {
+        assert_eq!(value, stand_pat);
+        assert!(search.pv_table[0].is_empty());
+    }
+}
+
+#[test]
+fn test_quiescence_finds_mate_in_check() {
+    // black is checkmated, in check the quiescence search must not stand pat
+    let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+    let mut evaluator = SmartEval::default();
+    let transp_table = TranspTable::new(1);
+    let mut time_manager = TimeManager::unlimited();
+    let mut search = new_search(&mut evaluator, &transp_table, &mut time_manager, &board);
+
+    assert_eq!(
+        search.quiescence(&board, 0, -Score::INFINITY, Score::INFINITY),
+        Score::mated_in(0)
+    );
+}
+
+#[test]
+fn test_search_sees_recapture_at_horizon() {
+    // Qxd5 wins a pawn at depth 1 but loses the queen to exd5
+    let (board, _, pv) = search_pv("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", 1);
+
+    assert_ne!(pv[0].to_san(&board), "Qxd5");
+}
+
+// best move and nodes of an iterative deepening search to depth
+fn search_with_params(fen: &str, depth: u8, params: SearchParams) -> (String, u64) {
+    let board = Board::from_fen(fen).unwrap();
+    let transp_table = TranspTable::new(1);
+    let mut time_manager =
+        TimeManager::new(&SearchLimits::from_depth(depth), board.active_player, None);
+    let mut evaluator = SmartEval::default();
+    let mut search = CachedSearch::new(
+        &mut evaluator,
+        params,
+        &transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+    let mut moves = board.get_legal_moves();
+    let mut previous = None;
+    for depth in 1..=depth {
+        let results = search
+            .search_root_aspiration(&board, &mut moves, depth, 1, false, previous)
+            .unwrap();
+        moves = super::best_first(&results, &moves);
+        previous = Some(results[0].eval);
+    }
+    let nodes = search.time_manager.nodes();
+    (moves[0].to_san(&board), nodes)
+}
+
+#[rstest]
+fn test_search_parts_keep_tactics(
+    #[values(
+        "null_move",
+        "late_move_reductions",
+        "reverse_futility",
+        "futility",
+        "check_extensions",
+        "aspiration_windows"
+    )]
+    part: &str,
+    #[values(
+        ("r5k1/5ppp/8/8/8/3R4/5PPP/3R2K1 w - - 0 1", "Rd8+"), // mate in 2
+        ("6k1/5ppp/8/8/8/8/1q3PPP/3R2K1 w - - 0 1", "Rd8#"),
+        ("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", "Rxd5"),
+    )]
+    position: (&str, &str),
+) {
+    let mut params = SearchParams::disabled();
+    match part {
+        "null_move" => params.null_move = true,
+        "late_move_reductions" => params.late_move_reductions = true,
+        "reverse_futility" => params.reverse_futility = true,
+        "futility" => params.futility = true,
+        "check_extensions" => params.check_extensions = true,
+        _ => params.aspiration_windows = true,
+    }
+    let (fen, best_move) = position;
+
+    assert_eq!(search_with_params(fen, 5, params).0, best_move);
+}
+
+#[test]
+fn test_pruning_searches_fewer_nodes() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let (_, nodes) = search_with_params(fen, 5, SearchParams::default());
+    let (_, plain_nodes) = search_with_params(fen, 5, SearchParams::disabled());
+
+    assert!(nodes < plain_nodes);
+}
+
+#[rstest]
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 0 1")]
+#[case("4k3/2p5/3n4/8/8/8/8/3RK3 w - - 0 1")]
+fn test_alpha_beta_matches_nega_max(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let depth = 3;
+    let mut ordering = OrderingTables::new(depth as usize);
+
+    assert_eq!(
+        alpha_beta(
+            &board,
+            depth,
+            0,
+            -Score::INFINITY,
+            Score::INFINITY,
+            &mut ordering
+        ),
+        nega_max(&board, depth, &mut get_material_eval)
+    );
+}
+
+#[test]
+fn test_alpha_beta_finds_mate() {
+    // 1. Rd8+ Rxd8 2. Rxd8#
+    let board = Board::from_fen("r5k1/5ppp/8/8/8/3R4/5PPP/3R2K1 w - - 0 1").unwrap();
+
+    assert_eq!(search_alpha_beta(&board, 3).to_san(&board), "Rd8+");
+}
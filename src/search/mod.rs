@@ -1,3 +1,9 @@
 pub mod eval;
+pub mod evaluator;
 pub mod minimax;
+pub mod move_picker;
+pub mod nnue;
 pub mod perft;
+pub mod score;
+pub mod search_params;
+pub mod time_management;
@@ -0,0 +1,293 @@
+use crate::{
+    board::{
+        model_utils::ColorProps,
+        models::{File, LegalMove, Move, Piece, PieceType, Square},
+        move_checking::{get_legal_move_from_move, is_promotion_move},
+        Board,
+    },
+    hashing::PackedMove,
+};
+
+use super::eval::piece_value;
+
+#[cfg(test)]
+mod tests;
+
+const MAX_HISTORY: i32 = 16384;
+const MOVE_KEYS: usize = 2 * 6 * 64;
+
+// The moving piece and its destination, the king's destination for castling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveKey(Piece, Square);
+
+impl MoveKey {
+    pub fn new(board: &Board, move_: &LegalMove) -> MoveKey {
+        let home_rank = board.active_player.home_rank();
+        let (src, dest) = match move_.to_move(board) {
+            Move::Normal { src, dest } | Move::Promotion { src, dest, .. } => (src, dest),
+            Move::CastleKingside => (Square(File::E, home_rank), Square(File::G, home_rank)),
+            Move::CastleQueenside => (Square(File::E, home_rank), Square(File::C, home_rank)),
+        };
+        MoveKey(board.get_piece_at(src).unwrap(), dest)
+    }
+
+    fn index(self) -> usize {
+        let MoveKey(Piece(piece, color), dest) = self;
+        (color as usize * 6 + piece as usize) * 64 + dest.to_index()
+    }
+}
+
+// captured material, a promotion gains the new piece and loses the pawn
+pub fn material_gain(move_: &LegalMove) -> i32 {
+    let promotion_gain = match move_ {
+        LegalMove::Promotion { promotion, .. } => {
+            piece_value(*promotion) - piece_value(PieceType::Pawn)
+        }
+        _ => 0,
+    };
+    move_.captured_piece().map_or(0, piece_value) + promotion_gain
+}
+
+// most valuable victim first, least valuable attacker among equal victims, quiet moves score 0 or less
+pub fn mvv_lva(board: &Board, move_: &LegalMove) -> i32 {
+    let attacker = match move_ {
+        LegalMove::Normal { src, .. } => board.get_piece_at(*src).map_or(0, |p| p.0 as i32),
+        _ => PieceType::Pawn as i32, // promotions and en passant captures
+    };
+    material_gain(move_) * 8 - attacker
+}
+
+// Captures and queen promotions (Board::get_capture_moves) are searched in the capture stages,
+// all other moves including underpromotions count as quiet
+pub fn is_quiet(move_: &LegalMove) -> bool {
+    match move_ {
+        LegalMove::Promotion { promotion, .. } => *promotion != PieceType::Queen,
+        _ => !move_.is_capture(),
+    }
+}
+
+/*
+Heuristics for quiet moves, collected during one search:
+killers: per ply, the last two quiet moves that caused a beta cutoff, they often refute sibling positions as well
+counter_moves: per previous move, the quiet reply that refuted it
+history: per move, how often it caused a cutoff, weighted by remaining depth. Quiet moves searched before the
+cutoff move are punished. Values saturate at MAX_HISTORY.
+*/
+pub struct OrderingTables {
+    killers: Vec<[Option<PackedMove>; 2]>,
+    counter_moves: Vec<Option<PackedMove>>,
+    history: Vec<i32>,
+}
+
+impl OrderingTables {
+    pub fn new(max_ply: usize) -> OrderingTables {
+        OrderingTables {
+            killers: vec![[None; 2]; max_ply + 1],
+            counter_moves: vec![None; MOVE_KEYS],
+            history: vec![0; MOVE_KEYS],
+        }
+    }
+
+    pub fn killers(&self, ply: usize) -> [Option<PackedMove>; 2] {
+        self.killers[ply]
+    }
+
+    pub fn counter_move(&self, previous: Option<MoveKey>) -> Option<PackedMove> {
+        previous.and_then(|key| self.counter_moves[key.index()])
+    }
+
+    pub fn history(&self, board: &Board, move_: &LegalMove) -> i32 {
+        self.history[MoveKey::new(board, move_).index()]
+    }
+
+    fn add_history(&mut self, board: &Board, move_: &LegalMove, bonus: i32) {
+        let entry = &mut self.history[MoveKey::new(board, move_).index()];
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+
+    // move_ caused a beta cutoff at ply after the quiet moves in searched_quiets failed to do so
+    // previous: the move that led to board
+    pub fn update_cutoff(
+        &mut self,
+        board: &Board,
+        move_: &LegalMove,
+        ply: usize,
+        depth: u8,
+        previous: Option<MoveKey>,
+        searched_quiets: &[LegalMove],
+    ) {
+        if !is_quiet(move_) {
+            return;
+        }
+        let packed = PackedMove::from_legal_move(move_, board);
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(packed) {
+            killers[1] = killers[0];
+            killers[0] = Some(packed);
+        }
+        if let Some(previous) = previous {
+            self.counter_moves[previous.index()] = Some(packed);
+        }
+        let bonus = (depth as i32 * depth as i32).min(MAX_HISTORY);
+        self.add_history(board, move_, bonus);
+        for quiet in searched_quiets.iter().filter(|quiet| *quiet != move_) {
+            self.add_history(board, quiet, -bonus);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    TtMove,
+    GenerateCaptures,
+    GoodCaptures,
+    Killers(usize),
+    CounterMove,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/*
+Returns the legal moves of a position in the order they should be searched:
+1. the transposition table move
+2. captures and queen promotions that do not lose material (SEE), by MVV-LVA
+3. the killer moves of the ply and the counter move to the previous move
+4. the remaining quiet moves by history
+5. captures that lose material
+Moves are only generated when a stage needs them, so a cutoff by the first moves saves the move generation.
+*/
+pub struct MovePicker {
+    stage: Stage,
+    tt_move: Option<PackedMove>,
+    killers: [Option<PackedMove>; 2],
+    counter_move: Option<PackedMove>,
+    picked: Vec<PackedMove>, // moves returned before their regular stage
+    scored: Vec<(LegalMove, i32)>,
+    bad_captures: Vec<(LegalMove, i32)>,
+}
+
+// the legal move for a move from another position, if it is legal here
+fn to_legal_move(board: &Board, packed: PackedMove) -> Option<LegalMove> {
+    let move_ = packed.to_move();
+    if let Move::Promotion { src, dest, .. } = move_ {
+        if !is_promotion_move(board, src, dest) {
+            return None;
+        }
+    }
+    get_legal_move_from_move(board, &move_)
+}
+
+// removes and returns the move with the highest score
+fn pop_best(scored: &mut Vec<(LegalMove, i32)>) -> Option<LegalMove> {
+    let best = scored
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, (_, score))| *score)?
+        .0;
+    Some(scored.swap_remove(best).0)
+}
+
+impl MovePicker {
+    // previous: the move that led to the position, for the counter move
+    pub fn new(
+        tt_move: Option<PackedMove>,
+        ply: usize,
+        previous: Option<MoveKey>,
+        tables: &OrderingTables,
+    ) -> MovePicker {
+        MovePicker {
+            stage: Stage::TtMove,
+            tt_move,
+            killers: tables.killers(ply),
+            counter_move: tables.counter_move(previous),
+            picked: Vec::new(),
+            scored: Vec::new(),
+            bad_captures: Vec::new(),
+        }
+    }
+
+    // a killer or counter move, if it is a legal quiet move that was not returned yet
+    fn pick_quiet(&mut self, board: &Board, packed: Option<PackedMove>) -> Option<LegalMove> {
+        let packed = packed.filter(|packed| !self.picked.contains(packed))?;
+        let move_ = to_legal_move(board, packed).filter(is_quiet)?;
+        self.picked.push(packed);
+        Some(move_)
+    }
+
+    pub fn next(&mut self, board: &Board, tables: &OrderingTables) -> Option<LegalMove> {
+        loop {
+            match self.stage {
+                Stage::TtMove => {
+                    self.stage = Stage::GenerateCaptures;
+                    if let Some(tt_move) = self.tt_move {
+                        if let Some(move_) = to_legal_move(board, tt_move) {
+                            self.picked.push(tt_move);
+                            return Some(move_);
+                        }
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    for move_ in board.get_capture_moves() {
+                        if self
+                            .picked
+                            .contains(&PackedMove::from_legal_move(&move_, board))
+                        {
+                            continue;
+                        }
+                        let score = mvv_lva(board, &move_);
+                        if board.see(&move_) < 0 {
+                            self.bad_captures.push((move_, score));
+                        } else {
+                            self.scored.push((move_, score));
+                        }
+                    }
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => match pop_best(&mut self.scored) {
+                    Some(move_) => return Some(move_),
+                    None => self.stage = Stage::Killers(0),
+                },
+                Stage::Killers(i) => {
+                    self.stage = if i + 1 < self.killers.len() {
+                        Stage::Killers(i + 1)
+                    } else {
+                        Stage::CounterMove
+                    };
+                    if let Some(move_) = self.pick_quiet(board, self.killers[i]) {
+                        return Some(move_);
+                    }
+                }
+                Stage::CounterMove => {
+                    self.stage = Stage::GenerateQuiets;
+                    if let Some(move_) = self.pick_quiet(board, self.counter_move) {
+                        return Some(move_);
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    for move_ in board.get_legal_moves().into_iter().filter(is_quiet) {
+                        if self
+                            .picked
+                            .contains(&PackedMove::from_legal_move(&move_, board))
+                        {
+                            continue;
+                        }
+                        let score = tables.history(board, &move_);
+                        self.scored.push((move_, score));
+                    }
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => match pop_best(&mut self.scored) {
+                    Some(move_) => return Some(move_),
+                    None => self.stage = Stage::BadCaptures,
+                },
+                Stage::BadCaptures => match pop_best(&mut self.bad_captures) {
+                    Some(move_) => return Some(move_),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use rstest::rstest;
+
+use crate::{
+    board::{
+        models::{LegalMove, Move},
+        test_utils::legal_move,
+        Board,
+    },
+    hashing::PackedMove,
+};
+
+use super::{MoveKey, MovePicker, OrderingTables};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn packed(board: &Board, uci_move: &str) -> PackedMove {
+    PackedMove::new(&Move::from_uci_string(board, uci_move).unwrap())
+}
+
+fn pick_all(picker: &mut MovePicker, board: &Board, tables: &OrderingTables) -> Vec<LegalMove> {
+    let mut moves = Vec::new();
+    while let Some(move_) = picker.next(board, tables) {
+        moves.push(move_);
+    }
+    moves
+}
+
+#[rstest]
+#[case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", None)]
+#[case(KIWIPETE, Some("e2a6"))]
+// the tt move is illegal here, e.g. after a hash collision
+#[case(KIWIPETE, Some("e2e4"))]
+#[case(
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 b kq - 0 1",
+    Some("b2a1n")
+)]
+#[case(
+    "rnbqkb1r/ppp1pppp/5n2/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+    Some("e5d6")
+)]
+fn test_picker_returns_each_legal_move_once(#[case] fen: &str, #[case] tt_move: Option<&str>) {
+    let board = Board::from_fen(fen).unwrap();
+    let mut tables = OrderingTables::new(4);
+    // killers that are illegal or captures in this position must be skipped
+    for killer in ["e1g1", "a2a4", "d5e6", "h1h8"] {
+        let move_ = PackedMove::new(&Move::from_uci_string(&board, killer).unwrap());
+        tables.killers[1][1] = tables.killers[1][0];
+        tables.killers[1][0] = Some(move_);
+    }
+    let tt_move = tt_move.map(|tt_move| packed(&board, tt_move));
+    let mut picker = MovePicker::new(tt_move, 1, None, &tables);
+    let mut picked = pick_all(&mut picker, &board, &tables);
+    let mut expected = board.get_legal_moves();
+    let key = |move_: &LegalMove| move_.to_move(&board).to_uci_string(&board);
+    picked.sort_by_key(key);
+    expected.sort_by_key(key);
+
+    assert_eq!(picked, expected);
+}
+
+// Good captures in kiwipete: Bxa6, gxh3, dxe6. Qxf6, Nxd7, Nxf7, Nxg6 and Qxh3 (Rxh3) lose material.
+const GOOD_CAPTURES: [&str; 3] = ["e2a6", "g2h3", "d5e6"];
+const BAD_CAPTURES: [&str; 5] = ["f3f6", "e5d7", "e5f7", "e5g6", "f3h3"];
+
+#[test]
+fn test_picker_order() {
+    let board = Board::from_fen(KIWIPETE).unwrap();
+    let mut tables = OrderingTables::new(4);
+    tables.update_cutoff(&board, &legal_move(&board, "a2a3"), 2, 3, None, &[]);
+    let mut picker = MovePicker::new(Some(packed(&board, "e1g1")), 2, None, &tables);
+    let moves = pick_all(&mut picker, &board, &tables);
+    let uci_moves: Vec<String> = moves
+        .iter()
+        .map(|move_| move_.to_move(&board).to_uci_string(&board))
+        .collect();
+
+    assert_eq!(uci_moves[0], "e1g1");
+    assert_eq!(uci_moves[1], "e2a6"); // most valuable victim
+    for capture in GOOD_CAPTURES {
+        assert!(uci_moves[1..4].contains(&capture.to_string()));
+    }
+    assert_eq!(uci_moves[4], "a2a3"); // killer
+    let bad_captures = &uci_moves[uci_moves.len() - BAD_CAPTURES.len()..];
+    assert_eq!(bad_captures[0], "f3f6");
+    for capture in BAD_CAPTURES {
+        assert!(bad_captures.contains(&capture.to_string()));
+    }
+}
+
+#[test]
+fn test_counter_move_follows_killers() {
+    let board = Board::from_fen(KIWIPETE).unwrap();
+    let black_to_move =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1")
+            .unwrap();
+    let previous = MoveKey::new(&black_to_move, &legal_move(&black_to_move, "a8b8"));
+    let mut tables = OrderingTables::new(4);
+    tables.update_cutoff(
+        &board,
+        &legal_move(&board, "g2g3"),
+        1,
+        3,
+        Some(previous),
+        &[],
+    );
+    tables.history.fill(0); // only the counter move may move g2g3 forward
+    let g2g3_position = |previous| {
+        // no killers at ply 2
+        let mut picker = MovePicker::new(None, 2, previous, &tables);
+        pick_all(&mut picker, &board, &tables)
+            .iter()
+            .position(|move_| *move_ == legal_move(&board, "g2g3"))
+            .unwrap()
+    };
+
+    assert_eq!(g2g3_position(Some(previous)), GOOD_CAPTURES.len());
+    assert!(g2g3_position(None) > GOOD_CAPTURES.len());
+}
+
+#[test]
+fn test_history_rewards_cutoffs_and_punishes_failed_quiets() {
+    let board = Board::default();
+    let mut tables = OrderingTables::new(4);
+    let cutoff = legal_move(&board, "e2e4");
+    let failed = legal_move(&board, "a2a3");
+    tables.update_cutoff(
+        &board,
+        &cutoff,
+        0,
+        4,
+        None,
+        &[failed.clone(), cutoff.clone()],
+    );
+
+    assert_eq!(tables.history(&board, &cutoff), 16);
+    assert_eq!(tables.history(&board, &failed), -16);
+    assert_eq!(tables.killers(0)[0], Some(packed(&board, "e2e4")));
+
+    // quiet moves are ordered by history
+    let mut picker = MovePicker::new(None, 1, None, &tables);
+    let moves = pick_all(&mut picker, &board, &tables);
+    assert_eq!(moves[0], cutoff);
+    assert_eq!(moves[moves.len() - 1], failed);
+}
@@ -0,0 +1,382 @@
+use std::sync::Arc;
+
+use crate::board::{
+    model_utils::ColorProps,
+    models::{Color, File, LegalMove, Piece, PieceType, Square},
+    Board,
+};
+
+use super::{evaluator::Evaluator, score::Score};
+
+mod simd;
+#[cfg(test)]
+mod tests;
+
+/*
+Efficiently updatable neural network: 768 inputs -> hidden layer per perspective -> 1 output.
+Input features, seen from one perspective (the color whose accumulator it is):
+    index = side * 384 + piece type * 64 + square
+    side: 0 for the pieces of the perspective, 1 for those of the opponent
+    piece type: pawn, knight, bishop, rook, queen, king (0 to 5)
+    square: a1 = 0, b1 = 1, ..., h8 = 63, mirrored vertically (square ^ 56) for black's perspective
+The accumulator of a perspective is the feature biases plus the weights of all active features.
+Moves change at most four features, so the accumulators are updated incrementally instead of recomputed.
+The output is computed from the accumulator of the side to move followed by that of the other side:
+    eval = (sum of clamp(accumulator, 0, QA) * output weight + output bias) * SCALE / (QA * QB) centipawns
+    clamped to +-Score::MAX_EVAL
+Quantization: feature weights and biases are scaled by QA, output weights by QB, the output bias by QA * QB.
+
+Weight file format, all numbers little endian:
+    magic            8 bytes, "OTUSNNUE"
+    version          u32, 1
+    hidden size      u32, neurons per perspective
+    feature weights  768 * hidden size i16, all weights of feature 0 first, then feature 1, ...
+    feature biases   hidden size i16
+    output weights   2 * hidden size i16, side to move first
+    output bias      i32
+*/
+pub const INPUTS: usize = 768;
+pub const QA: i32 = 255;
+pub const QB: i32 = 64;
+const SCALE: i32 = 400;
+const MAGIC: &[u8; 8] = b"OTUSNNUE";
+const VERSION: u32 = 1;
+const MAX_HIDDEN: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+// Reads the little endian numbers of a weight file in order
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize, what: &str) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(format!("Network file ends in {}", what));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self, what: &str) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4, what)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self, what: &str) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4, what)?.try_into().unwrap()))
+    }
+
+    fn i16s(&mut self, len: usize, what: &str) -> Result<Vec<i16>, String> {
+        Ok(self
+            .take(2 * len, what)?
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect())
+    }
+}
+
+impl Network {
+    // the weights in the layout of the weight file, see above
+    pub fn new(
+        feature_weights: Vec<i16>,
+        feature_biases: Vec<i16>,
+        output_weights: Vec<i16>,
+        output_bias: i32,
+    ) -> Result<Network, String> {
+        let hidden = feature_biases.len();
+        if hidden == 0 || hidden > MAX_HIDDEN {
+            return Err(format!(
+                "Hidden size {} is out of range 1..={}",
+                hidden, MAX_HIDDEN
+            ));
+        }
+        if feature_weights.len() != INPUTS * hidden || output_weights.len() != 2 * hidden {
+            return Err(format!(
+                "Weights do not match hidden size {}, expected {} feature and {} output weights",
+                hidden,
+                INPUTS * hidden,
+                2 * hidden
+            ));
+        }
+        Ok(Network {
+            hidden,
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Network, String> {
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Could not read network {}: {}", path, e))?;
+        Network::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Network, String> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len(), "magic")? != MAGIC {
+            return Err("Not an otus network file".to_string());
+        }
+        let version = reader.u32("version")?;
+        if version != VERSION {
+            return Err(format!("Unsupported network version {}", version));
+        }
+        let hidden = reader.u32("hidden size")? as usize;
+        if hidden == 0 || hidden > MAX_HIDDEN {
+            return Err(format!(
+                "Hidden size {} is out of range 1..={}",
+                hidden, MAX_HIDDEN
+            ));
+        }
+        let feature_weights = reader.i16s(INPUTS * hidden, "feature weights")?;
+        let feature_biases = reader.i16s(hidden, "feature biases")?;
+        let output_weights = reader.i16s(2 * hidden, "output weights")?;
+        let output_bias = reader.i32("output bias")?;
+        if !reader.bytes.is_empty() {
+            return Err(format!(
+                "Network file has {} bytes after the output bias",
+                reader.bytes.len()
+            ));
+        }
+        Network::new(feature_weights, feature_biases, output_weights, output_bias)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.hidden as u32).to_le_bytes());
+        for weights in [
+            &self.feature_weights,
+            &self.feature_biases,
+            &self.output_weights,
+        ] {
+            bytes.extend(weights.iter().flat_map(|weight| weight.to_le_bytes()));
+        }
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+        bytes
+    }
+
+    pub fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    fn feature_weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+
+    // accumulators: of the side to move, then of the other side
+    fn output(&self, own: &[i16], other: &[i16]) -> Score {
+        let (own_weights, other_weights) = self.output_weights.split_at(self.hidden);
+        let sum = simd::clipped_dot(own, own_weights, QA as i16)
+            + simd::clipped_dot(other, other_weights, QA as i16)
+            + self.output_bias as i64;
+        // extreme weights must neither reach the mate scores nor overflow the transposition table
+        let eval = (sum * SCALE as i64 / (QA * QB) as i64)
+            .clamp(-Score::MAX_EVAL.0 as i64, Score::MAX_EVAL.0 as i64);
+        Score(eval as i32)
+    }
+}
+
+fn feature_index(perspective: Color, Piece(piece, color): Piece, square: Square) -> usize {
+    let side = usize::from(color != perspective);
+    let square = match perspective {
+        Color::White => square.to_index(),
+        Color::Black => square.to_index() ^ 56,
+    };
+    side * 384 + piece as usize * 64 + square
+}
+
+type Features = Vec<(Piece, Square)>;
+
+// pieces leaving and entering squares in a move of board.active_player: (removed, added)
+fn changed_features(board: &Board, move_: &LegalMove) -> (Features, Features) {
+    let color = board.active_player;
+    let pawn = Piece(PieceType::Pawn, color);
+    let captured = |piece: &Option<PieceType>, square: Square| {
+        piece.map(|piece| (Piece(piece, color.opponent()), square))
+    };
+    let castle = |king_file: File, rook_src: File, rook_dest: File| {
+        let king = Piece(PieceType::King, color);
+        let rook = Piece(PieceType::Rook, color);
+        let rank = color.home_rank();
+        (
+            vec![
+                (king, color.king_home_square()),
+                (rook, Square(rook_src, rank)),
+            ],
+            vec![
+                (king, Square(king_file, rank)),
+                (rook, Square(rook_dest, rank)),
+            ],
+        )
+    };
+    match move_ {
+        LegalMove::Normal {
+            src,
+            dest,
+            captured_piece,
+            ..
+        } => {
+            let piece = board.get_piece_at(*src).expect("No piece to move");
+            let mut removed = vec![(piece, *src)];
+            removed.extend(captured(captured_piece, *dest));
+            (removed, vec![(piece, *dest)])
+        }
+        LegalMove::DoublePawnPush { file, .. } => (
+            vec![(pawn, Square(*file, color.pawn_start_rank()))],
+            vec![(pawn, Square(*file, color.double_push_rank()))],
+        ),
+        LegalMove::CastleKingside { .. } => castle(File::G, File::H, File::F),
+        LegalMove::CastleQueenside { .. } => castle(File::C, File::A, File::D),
+        LegalMove::Promotion {
+            src,
+            dest,
+            promotion,
+            captured_piece,
+            ..
+        } => {
+            let mut removed = vec![(pawn, *src)];
+            removed.extend(captured(captured_piece, *dest));
+            (removed, vec![(Piece(*promotion, color), *dest)])
+        }
+        LegalMove::EnPassantCapture { src, dest, .. } => (
+            vec![
+                (pawn, *src),
+                (
+                    Piece(PieceType::Pawn, color.opponent()),
+                    Square(dest.0, color.opponent().double_push_rank()),
+                ),
+            ],
+            vec![(pawn, *dest)],
+        ),
+    }
+}
+
+/*
+Evaluator for a Network. The accumulators of all positions on the current search path are kept in a stack,
+make_move derives the next ones from the top, unmake_move drops them again.
+Before reset is called, evaluate computes the accumulators of the board from scratch.
+*/
+#[derive(Clone)]
+pub struct NnueEval {
+    network: Arc<Network>,
+    accumulators: Vec<i16>, // per ply: white's accumulator, then black's, 2 * hidden values
+    ply: Option<usize>,     // position of the top of the stack, None before reset
+}
+
+impl NnueEval {
+    pub fn new(network: Arc<Network>) -> NnueEval {
+        NnueEval {
+            network,
+            accumulators: Vec::new(),
+            ply: None,
+        }
+    }
+
+    fn accumulator_mut(&mut self, ply: usize) -> &mut [i16] {
+        let size = 2 * self.network.hidden;
+        if self.accumulators.len() < (ply + 1) * size {
+            self.accumulators.resize((ply + 1) * size, 0);
+        }
+        &mut self.accumulators[ply * size..(ply + 1) * size]
+    }
+
+    // computes the accumulators of board from scratch into the stack at ply
+    fn refresh(&mut self, board: &Board, ply: usize) {
+        let network = Arc::clone(&self.network);
+        let accumulator = self.accumulator_mut(ply);
+        let (white, black) = accumulator.split_at_mut(network.hidden);
+        white.copy_from_slice(&network.feature_biases);
+        black.copy_from_slice(&network.feature_biases);
+        for square in board.occupancy() {
+            if let Some(piece) = board.get_piece_at(square) {
+                simd::add_assign(
+                    white,
+                    network.feature_weights(feature_index(Color::White, piece, square)),
+                );
+                simd::add_assign(
+                    black,
+                    network.feature_weights(feature_index(Color::Black, piece, square)),
+                );
+            }
+        }
+    }
+
+    fn evaluate_at(&self, board: &Board, ply: usize) -> Score {
+        let hidden = self.network.hidden;
+        let accumulator = &self.accumulators[2 * hidden * ply..2 * hidden * (ply + 1)];
+        let (white, black) = accumulator.split_at(hidden);
+        match board.active_player {
+            Color::White => self.network.output(white, black),
+            Color::Black => self.network.output(black, white),
+        }
+    }
+}
+
+impl Evaluator for NnueEval {
+    fn evaluate(&mut self, board: &Board) -> Score {
+        match self.ply {
+            Some(ply) => self.evaluate_at(board, ply),
+            None => {
+                self.refresh(board, 0);
+                self.evaluate_at(board, 0)
+            }
+        }
+    }
+
+    fn reset(&mut self, board: &Board) {
+        self.refresh(board, 0);
+        self.ply = Some(0);
+    }
+
+    fn make_move(&mut self, board: &Board, move_: &LegalMove) {
+        let Some(ply) = self.ply else {
+            return;
+        };
+        let network = Arc::clone(&self.network);
+        let size = 2 * network.hidden;
+        self.accumulator_mut(ply + 1);
+        let (parent, child) = self.accumulators.split_at_mut((ply + 1) * size);
+        let child = &mut child[..size];
+        child.copy_from_slice(&parent[ply * size..]);
+        let (white, black) = child.split_at_mut(network.hidden);
+        let (removed, added) = changed_features(board, move_);
+        for (piece, square) in removed {
+            simd::sub_assign(
+                white,
+                network.feature_weights(feature_index(Color::White, piece, square)),
+            );
+            simd::sub_assign(
+                black,
+                network.feature_weights(feature_index(Color::Black, piece, square)),
+            );
+        }
+        for (piece, square) in added {
+            simd::add_assign(
+                white,
+                network.feature_weights(feature_index(Color::White, piece, square)),
+            );
+            simd::add_assign(
+                black,
+                network.feature_weights(feature_index(Color::Black, piece, square)),
+            );
+        }
+        self.ply = Some(ply + 1);
+    }
+
+    fn unmake_move(&mut self, _board: &Board, _move: &LegalMove) {
+        if let Some(ply) = self.ply {
+            self.ply = Some(ply - 1);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+/*
+Vector kernels of the network, with AVX2 on x86_64 CPUs that support it and a scalar fallback.
+The accumulator updates of both wrap around on overflow in the same way, so they give the same results for all inputs.
+Dot products are summed in i64, which cannot overflow for any network of up to MAX_HIDDEN neurons.
+*/
+
+pub fn add_assign(accumulator: &mut [i16], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2
+        return unsafe { avx2::add_assign(accumulator, weights) };
+    }
+    scalar::add_assign(accumulator, weights);
+}
+
+pub fn sub_assign(accumulator: &mut [i16], weights: &[i16]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2
+        return unsafe { avx2::sub_assign(accumulator, weights) };
+    }
+    scalar::sub_assign(accumulator, weights);
+}
+
+// sum of clamp(accumulator, 0, max) * weights
+pub fn clipped_dot(accumulator: &[i16], weights: &[i16], max: i16) -> i64 {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2
+        return unsafe { avx2::clipped_dot(accumulator, weights, max) };
+    }
+    scalar::clipped_dot(accumulator, weights, max)
+}
+
+pub mod scalar {
+    pub fn add_assign(accumulator: &mut [i16], weights: &[i16]) {
+        for (value, weight) in accumulator.iter_mut().zip(weights) {
+            *value = value.wrapping_add(*weight);
+        }
+    }
+
+    pub fn sub_assign(accumulator: &mut [i16], weights: &[i16]) {
+        for (value, weight) in accumulator.iter_mut().zip(weights) {
+            *value = value.wrapping_sub(*weight);
+        }
+    }
+
+    pub fn clipped_dot(accumulator: &[i16], weights: &[i16], max: i16) -> i64 {
+        accumulator
+            .iter()
+            .zip(weights)
+            .map(|(value, weight)| (*value).clamp(0, max) as i64 * *weight as i64)
+            .sum()
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 16; // i16 values per 256 bit register
+
+    // Safety: the CPU must support AVX2
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn add_assign(accumulator: &mut [i16], weights: &[i16]) {
+        let len = accumulator.len().min(weights.len());
+        let chunks = len / LANES;
+        for chunk in 0..chunks {
+            let values = accumulator.as_mut_ptr().add(chunk * LANES) as *mut __m256i;
+            let chunk_weights = weights.as_ptr().add(chunk * LANES) as *const __m256i;
+            _mm256_storeu_si256(
+                values,
+                _mm256_add_epi16(
+                    _mm256_loadu_si256(values),
+                    _mm256_loadu_si256(chunk_weights),
+                ),
+            );
+        }
+        super::scalar::add_assign(
+            &mut accumulator[chunks * LANES..len],
+            &weights[chunks * LANES..len],
+        );
+    }
+
+    // Safety: the CPU must support AVX2
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sub_assign(accumulator: &mut [i16], weights: &[i16]) {
+        let len = accumulator.len().min(weights.len());
+        let chunks = len / LANES;
+        for chunk in 0..chunks {
+            let values = accumulator.as_mut_ptr().add(chunk * LANES) as *mut __m256i;
+            let chunk_weights = weights.as_ptr().add(chunk * LANES) as *const __m256i;
+            _mm256_storeu_si256(
+                values,
+                _mm256_sub_epi16(
+                    _mm256_loadu_si256(values),
+                    _mm256_loadu_si256(chunk_weights),
+                ),
+            );
+        }
+        super::scalar::sub_assign(
+            &mut accumulator[chunks * LANES..len],
+            &weights[chunks * LANES..len],
+        );
+    }
+
+    // Safety: the CPU must support AVX2
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn clipped_dot(accumulator: &[i16], weights: &[i16], max: i16) -> i64 {
+        let len = accumulator.len().min(weights.len());
+        let chunks = len / LANES;
+        let zero = _mm256_setzero_si256();
+        let max_values = _mm256_set1_epi16(max);
+        let mut sums = [_mm256_setzero_si256(); 2]; // eight i64 sums
+        for chunk in 0..chunks {
+            let values =
+                _mm256_loadu_si256(accumulator.as_ptr().add(chunk * LANES) as *const __m256i);
+            let chunk_weights =
+                _mm256_loadu_si256(weights.as_ptr().add(chunk * LANES) as *const __m256i);
+            let clipped = _mm256_min_epi16(_mm256_max_epi16(values, zero), max_values);
+            // multiplies the i16 pairs and adds neighbouring products to i32, which fits a single chunk
+            let products = _mm256_madd_epi16(clipped, chunk_weights);
+            // widened to i64 before summing up the chunks
+            sums[0] = _mm256_add_epi64(
+                sums[0],
+                _mm256_cvtepi32_epi64(_mm256_castsi256_si128(products)),
+            );
+            sums[1] = _mm256_add_epi64(
+                sums[1],
+                _mm256_cvtepi32_epi64(_mm256_extracti128_si256(products, 1)),
+            );
+        }
+        let mut lanes = [0i64; 8];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sums[0]);
+        _mm256_storeu_si256(lanes.as_mut_ptr().add(4) as *mut __m256i, sums[1]);
+        lanes.iter().sum::<i64>()
+            + super::scalar::clipped_dot(
+                &accumulator[chunks * LANES..len],
+                &weights[chunks * LANES..len],
+                max,
+            )
+    }
+}
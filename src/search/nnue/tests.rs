@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rstest::rstest;
+
+use crate::{
+    board::{models::LegalMove, Board},
+    game::PositionHistory,
+    hashing::TranspTable,
+    search::{evaluator::Evaluator, minimax::search_minimax_cached, score::Score},
+};
+
+use super::{simd, Network, NnueEval, INPUTS};
+
+fn random_weights(rng: &mut StdRng, len: usize) -> Vec<i16> {
+    (0..len).map(|_| rng.gen_range(-100..100)).collect()
+}
+
+// hidden sizes that are no multiple of the SIMD width also test the scalar remainder
+fn random_network(hidden: usize) -> Network {
+    let mut rng = StdRng::seed_from_u64(hidden as u64);
+    Network::new(
+        random_weights(&mut rng, INPUTS * hidden),
+        random_weights(&mut rng, hidden),
+        random_weights(&mut rng, 2 * hidden),
+        rng.gen_range(-10000..10000),
+    )
+    .unwrap()
+}
+
+// Compares every incrementally updated eval with one computed from scratch
+#[derive(Clone)]
+struct CheckedNnue {
+    incremental: NnueEval,
+    network: Arc<Network>,
+}
+
+impl Evaluator for CheckedNnue {
+    fn evaluate(&mut self, board: &Board) -> Score {
+        let eval = self.incremental.evaluate(board);
+        assert_eq!(
+            eval,
+            NnueEval::new(Arc::clone(&self.network)).evaluate(board)
+        );
+        eval
+    }
+
+    fn reset(&mut self, board: &Board) {
+        self.incremental.reset(board);
+    }
+
+    fn make_move(&mut self, board: &Board, move_: &LegalMove) {
+        self.incremental.make_move(board, move_);
+    }
+
+    fn unmake_move(&mut self, board: &Board, move_: &LegalMove) {
+        self.incremental.unmake_move(board, move_);
+    }
+}
+
+#[rstest]
+// castling, en passant
+#[case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+#[case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+// promotions with captures
+#[case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P1RPP/R2Q2K1 w kq - 0 1")]
+#[case("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1")]
+fn test_incremental_updates_match_refresh(#[case] fen: &str) {
+    let board = Board::from_fen(fen).unwrap();
+    let network = Arc::new(random_network(24));
+    let evaluator = CheckedNnue {
+        incremental: NnueEval::new(Arc::clone(&network)),
+        network,
+    };
+    let mut transp_table = TranspTable::new(1);
+
+    search_minimax_cached(
+        &board,
+        3,
+        evaluator,
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+    );
+}
+
+#[test]
+fn test_eval_is_symmetric() {
+    let mut evaluator = NnueEval::new(Arc::new(random_network(32)));
+    let white = Board::from_fen("4k3/8/8/8/8/2N5/4P3/4K3 w - - 0 1").unwrap();
+    let black = Board::from_fen("4k3/4p3/2n5/8/8/8/8/4K3 b - - 0 1").unwrap();
+
+    assert_eq!(evaluator.evaluate(&white), evaluator.evaluate(&black));
+}
+
+#[rstest]
+#[case(i16::MAX, i32::MAX, Score::MAX_EVAL)]
+#[case(i16::MIN, i32::MIN, -Score::MAX_EVAL)]
+fn test_extreme_weights_are_clamped(
+    #[case] weight: i16,
+    #[case] output_bias: i32,
+    #[case] expected: Score,
+) {
+    let hidden = 4096;
+    let network = Network::new(
+        vec![i16::MAX; INPUTS * hidden],
+        vec![i16::MAX; hidden],
+        vec![weight; 2 * hidden],
+        output_bias,
+    )
+    .unwrap();
+    let mut evaluator = NnueEval::new(Arc::new(network));
+
+    let eval = evaluator.evaluate(&Board::default());
+    assert_eq!(eval, expected);
+    assert!(!eval.is_mate());
+}
+
+#[test]
+fn test_save_and_load() {
+    let network = random_network(8);
+    let path = std::env::temp_dir().join(format!("otus_nnue_{}.bin", std::process::id()));
+    std::fs::write(&path, network.to_bytes()).unwrap();
+    let loaded = Network::load(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), network);
+    assert_eq!(
+        network.to_bytes().len(),
+        8 + 4 + 4 + 2 * (INPUTS + 3) * 8 + 4
+    );
+    assert!(Network::load("does/not/exist.bin").is_err());
+}
+
+#[rstest]
+#[case(|bytes: &mut Vec<u8>| bytes[0] = b'X')] // magic
+#[case(|bytes: &mut Vec<u8>| bytes[8] = 2)] // version
+#[case(|bytes: &mut Vec<u8>| bytes[12..16].copy_from_slice(&0u32.to_le_bytes()))] // hidden size
+#[case(|bytes: &mut Vec<u8>| bytes[12..16].copy_from_slice(&9u32.to_le_bytes()))]
+#[case(|bytes: &mut Vec<u8>| { bytes.pop(); })]
+#[case(|bytes: &mut Vec<u8>| bytes.push(0))]
+#[case(|bytes: &mut Vec<u8>| bytes.truncate(10))]
+fn test_invalid_files_are_rejected(#[case] corrupt: fn(&mut Vec<u8>)) {
+    let mut bytes = random_network(8).to_bytes();
+    corrupt(&mut bytes);
+
+    assert!(Network::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_invalid_networks_are_rejected() {
+    assert!(Network::new(vec![], vec![], vec![], 0).is_err());
+    assert!(Network::new(vec![0; INPUTS * 4], vec![0; 4], vec![0; 4], 0).is_err());
+    assert!(Network::new(vec![0; INPUTS * 3], vec![0; 4], vec![0; 8], 0).is_err());
+}
+
+#[rstest]
+#[case(16)]
+#[case(37)] // with a remainder after the full SIMD registers
+#[case(5)]
+fn test_simd_matches_scalar(#[case] len: usize) {
+    let mut rng = StdRng::seed_from_u64(len as u64);
+    let mut values: Vec<i16> = (0..len).map(|_| rng.gen()).collect();
+    values[0] = i16::MAX; // wraps around when adding
+    let weights: Vec<i16> = (0..len).map(|_| rng.gen()).collect();
+
+    let (mut simd_sum, mut scalar_sum) = (values.clone(), values.clone());
+    simd::add_assign(&mut simd_sum, &weights);
+    simd::scalar::add_assign(&mut scalar_sum, &weights);
+    assert_eq!(simd_sum, scalar_sum);
+
+    let (mut simd_difference, mut scalar_difference) = (values.clone(), values.clone());
+    simd::sub_assign(&mut simd_difference, &weights);
+    simd::scalar::sub_assign(&mut scalar_difference, &weights);
+    assert_eq!(simd_difference, scalar_difference);
+
+    assert_eq!(
+        simd::clipped_dot(&values, &weights, 255),
+        simd::scalar::clipped_dot(&values, &weights, 255)
+    );
+}
+
+#[test]
+fn test_clipped_dot_does_not_overflow() {
+    let len = 4096;
+    let (values, weights) = (vec![i16::MAX; len], vec![i16::MAX; len]);
+    let expected = len as i64 * 255 * i16::MAX as i64;
+
+    assert_eq!(simd::clipped_dot(&values, &weights, 255), expected);
+    assert_eq!(simd::scalar::clipped_dot(&values, &weights, 255), expected);
+}
@@ -0,0 +1,109 @@
+use std::{
+    fmt,
+    ops::{Add, Neg, Sub},
+};
+
+#[cfg(test)]
+mod tests;
+
+/*
+Evaluation from the view of the side to move.
+Normal scores are centipawns, scores close to +-MATE encode a forced mate:
+MATE - n means the side to move mates in n plies from the root, -MATE + n means it gets mated in n plies.
+Inside the transposition table mate scores are stored relative to the node instead of the root (see to_tt/from_tt),
+so that an entry stays correct when the position is reached at a different ply.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Score(pub i32);
+
+const MAX_MATE_PLY: i32 = 1000;
+
+impl Score {
+    pub const ZERO: Score = Score(0);
+    pub const MATE: Score = Score(30000);
+    // bound for alpha-beta windows, never the value of a position
+    pub const INFINITY: Score = Score(30001);
+    // bound for static evaluations, far enough from the mate scores that they are never taken for one
+    pub const MAX_EVAL: Score = Score(Score::MATE.0 - 2 * MAX_MATE_PLY);
+
+    // the side to move is checkmated, ply plies from the root
+    pub fn mated_in(ply: usize) -> Score {
+        Score(-Score::MATE.0 + ply as i32)
+    }
+
+    // the side to move delivers mate ply plies from the root
+    pub fn mate_in(ply: usize) -> Score {
+        Score(Score::MATE.0 - ply as i32)
+    }
+
+    pub fn is_mate(self) -> bool {
+        self.0.abs() > Score::MATE.0 - MAX_MATE_PLY && self.0.abs() <= Score::MATE.0
+    }
+
+    // Full moves until mate, negative if the side to move gets mated
+    pub fn mate_moves(self) -> Option<i32> {
+        if !self.is_mate() {
+            None
+        } else if self.0 > 0 {
+            Some((Score::MATE.0 - self.0 + 1) / 2)
+        } else {
+            Some(-(Score::MATE.0 + self.0 + 1) / 2)
+        }
+    }
+
+    // converts a score relative to the root into one relative to the node at ply, for storing
+    pub fn to_tt(self, ply: usize) -> Score {
+        match self {
+            score if score.is_mate() && score.0 > 0 => Score(score.0 + ply as i32),
+            score if score.is_mate() => Score(score.0 - ply as i32),
+            score => score,
+        }
+    }
+
+    // inverse of to_tt
+    pub fn from_tt(self, ply: usize) -> Score {
+        match self {
+            score if score.is_mate() && score.0 > 0 => Score(score.0 - ply as i32),
+            score if score.is_mate() => Score(score.0 + ply as i32),
+            score => score,
+        }
+    }
+
+    // "cp <centipawns>" or "mate <moves>" as used in UCI info lines
+    pub fn to_uci_string(self) -> String {
+        match self.mate_moves() {
+            Some(moves) => format!("mate {}", moves),
+            None => format!("cp {}", self.0),
+        }
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Self::Output {
+        Score(-self.0)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Score(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Score(self.0 - rhs.0)
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_uci_string())
+    }
+}
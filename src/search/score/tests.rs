@@ -0,0 +1,44 @@
+use rstest::rstest;
+
+use super::Score;
+
+#[rstest]
+#[case(Score(35), "cp 35")]
+#[case(Score(-1200), "cp -1200")]
+#[case(Score::mate_in(1), "mate 1")]
+#[case(Score::mate_in(3), "mate 2")]
+#[case(Score::mate_in(5), "mate 3")]
+#[case(Score::mated_in(2), "mate -1")]
+#[case(Score::mated_in(4), "mate -2")]
+fn test_uci_string(#[case] score: Score, #[case] expected: &str) {
+    assert_eq!(score.to_uci_string(), expected);
+}
+
+#[test]
+fn test_mate_ordering() {
+    assert!(Score::mate_in(1) > Score::mate_in(3));
+    assert!(Score::mate_in(99) > Score(5000));
+    assert!(Score::mated_in(2) < Score::mated_in(4));
+    assert!(Score::mated_in(0) > -Score::INFINITY);
+    assert_eq!(-Score::mate_in(3), Score::mated_in(3));
+    assert!(!Score(2500).is_mate());
+    assert!(!Score::INFINITY.is_mate());
+}
+
+#[rstest]
+#[case(Score(120), 7)]
+#[case(Score::mate_in(9), 4)]
+#[case(Score::mated_in(6), 3)]
+fn test_tt_roundtrip(#[case] score: Score, #[case] ply: usize) {
+    assert_eq!(score.to_tt(ply).from_tt(ply), score);
+}
+
+#[test]
+fn test_tt_score_is_relative_to_node() {
+    // mate in 5 plies from the root, found at ply 2: the node itself mates in 3 plies
+    assert_eq!(Score::mate_in(5).to_tt(2), Score::mate_in(3));
+    // reached again at ply 4, it is a mate in 7 plies from the root
+    assert_eq!(Score::mate_in(3).from_tt(4), Score::mate_in(7));
+    assert_eq!(Score::mated_in(4).to_tt(3), Score::mated_in(1));
+    assert_eq!(Score(-80).to_tt(3), Score(-80));
+}
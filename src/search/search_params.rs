@@ -0,0 +1,46 @@
+/*
+Switches for the selective parts of the search, so that each can be measured against a search without it.
+null_move: after passing the turn, a reduced search still fails high, so the position is not searched further.
+    Not used without pieces besides pawns, where zugzwang makes passing the turn an advantage.
+late_move_reductions: quiet moves late in the move order are searched with reduced depth first.
+reverse_futility: close to the horizon, a static eval far above beta fails high without a search.
+futility: close to the horizon, quiet moves are skipped if the static eval is far below alpha.
+check_extensions: positions in check are searched one ply deeper.
+aspiration_windows: the root is searched with a narrow window around the score of the previous iteration.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchParams {
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility: bool,
+    pub futility: bool,
+    pub check_extensions: bool,
+    pub aspiration_windows: bool,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchParams {
+            null_move: true,
+            late_move_reductions: true,
+            reverse_futility: true,
+            futility: true,
+            check_extensions: true,
+            aspiration_windows: true,
+        }
+    }
+}
+
+impl SearchParams {
+    // plain alpha-beta with quiescence search
+    pub fn disabled() -> SearchParams {
+        SearchParams {
+            null_move: false,
+            late_move_reductions: false,
+            reverse_futility: false,
+            futility: false,
+            check_extensions: false,
+            aspiration_windows: false,
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::Receiver,
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::board::models::Color;
+
+#[cfg(test)]
+mod tests;
+
+pub const MAX_DEPTH: u8 = 64;
+const DEFAULT_MOVES_TO_GO: u32 = 30; // assumed number of remaining moves in sudden death time controls
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30); // reserved for communication with the GUI
+const CHECK_INTERVAL: u64 = 1024; // nodes between two checks of the clock and the stop channel
+
+// Arguments of the UCI "go" command, all times in milliseconds
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchLimits {
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<u64>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u8>, // search for a mate in this many moves
+    pub infinite: bool,   // search until "stop", even if a limit above is reached
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<&&str>) -> Result<T, String> {
+    value
+        .and_then(|v| v.parse().ok())
+        .ok_or(format!("Invalid value for {}", name))
+}
+
+impl SearchLimits {
+    pub fn from_depth(depth: u8) -> SearchLimits {
+        SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    // arguments: tokens after "go", unknown tokens are ignored
+    pub fn from_uci(arguments: &[&str]) -> Result<SearchLimits, String> {
+        let mut limits = SearchLimits::default();
+        let mut tokens = arguments.iter();
+        while let Some(token) = tokens.next() {
+            match *token {
+                "wtime" => limits.wtime = Some(parse_value(token, tokens.next())?),
+                "btime" => limits.btime = Some(parse_value(token, tokens.next())?),
+                "winc" => limits.winc = Some(parse_value(token, tokens.next())?),
+                "binc" => limits.binc = Some(parse_value(token, tokens.next())?),
+                "movestogo" => limits.movestogo = Some(parse_value(token, tokens.next())?),
+                "movetime" => limits.movetime = Some(parse_value(token, tokens.next())?),
+                "depth" => limits.depth = Some(parse_value(token, tokens.next())?),
+                "nodes" => limits.nodes = Some(parse_value(token, tokens.next())?),
+                "mate" => limits.mate = Some(parse_value(token, tokens.next())?),
+                "infinite" => limits.infinite = true,
+                _ => (), // ponder, searchmoves and their moves
+            }
+        }
+        Ok(limits)
+    }
+}
+
+// Shared by the time managers of the main search thread and its helper threads
+#[derive(Debug, Default)]
+struct SharedState {
+    stop: AtomicBool, // set when the main thread is done, helpers stop at their next check
+    nodes: AtomicU64, // nodes of all threads, each thread adds its count every CHECK_INTERVAL nodes
+}
+
+/*
+Decides when the search has to stop.
+The soft limit is checked between iterations of iterative deepening: a new iteration takes several times as long as
+the previous one, so none is started once half of the move budget is used up.
+The hard limit, the node limit and the stop channel are checked during the search and abort it.
+The node limit counts the nodes of all threads.
+Helper threads of a multithreaded search have no limits of their own, they run until the main thread stops them.
+*/
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+    max_depth: u8,
+    max_nodes: Option<u64>,
+    infinite: bool,
+    rx: Option<Receiver<()>>,
+    nodes: u64,
+    shared_nodes: u64, // part of nodes already added to shared.nodes
+    shared: Arc<SharedState>,
+    stopped: bool,
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, player: Color, rx: Option<Receiver<()>>) -> TimeManager {
+        let (time, increment) = match player {
+            Color::White => (limits.wtime, limits.winc),
+            Color::Black => (limits.btime, limits.binc),
+        };
+        let (soft_limit, hard_limit) = match (limits.movetime, time) {
+            _ if limits.infinite => (None, None),
+            (Some(movetime), _) => {
+                let limit = Duration::from_millis(movetime).saturating_sub(MOVE_OVERHEAD);
+                (Some(limit), Some(limit))
+            }
+            (None, Some(time)) => {
+                let available = Duration::from_millis(time).saturating_sub(MOVE_OVERHEAD);
+                let moves_to_go = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let increment = Duration::from_millis(increment.unwrap_or(0));
+                let optimal = (available / moves_to_go + increment * 3 / 4).min(available);
+                (Some(optimal / 2), Some((optimal * 3).min(available)))
+            }
+            (None, None) => (None, None),
+        };
+        let mate_depth = limits
+            .mate
+            .map(|moves| moves.saturating_mul(2).saturating_sub(1));
+        let max_depth = match (limits.depth, mate_depth) {
+            _ if limits.infinite => MAX_DEPTH,
+            (Some(depth), Some(mate_depth)) => depth.min(mate_depth),
+            (Some(depth), None) | (None, Some(depth)) => depth,
+            (None, None) => MAX_DEPTH,
+        };
+        TimeManager {
+            start: Instant::now(),
+            soft_limit,
+            hard_limit,
+            max_depth: max_depth.clamp(1, MAX_DEPTH),
+            max_nodes: if limits.infinite { None } else { limits.nodes },
+            infinite: limits.infinite,
+            rx,
+            nodes: 0,
+            shared_nodes: 0,
+            shared: Arc::new(SharedState::default()),
+            stopped: false,
+        }
+    }
+
+    // Time manager for a helper thread, stopped by stop_helpers
+    pub fn helper(&self) -> TimeManager {
+        TimeManager {
+            start: self.start,
+            soft_limit: None,
+            hard_limit: None,
+            max_depth: self.max_depth,
+            max_nodes: None,
+            infinite: false,
+            rx: None,
+            nodes: 0,
+            shared_nodes: 0,
+            shared: Arc::clone(&self.shared),
+            stopped: false,
+        }
+    }
+
+    pub fn stop_helpers(&self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+    }
+
+    // No limits at all, the search depth is given explicitly
+    pub fn unlimited() -> TimeManager {
+        TimeManager::new(&SearchLimits::default(), Color::White, None)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    // nodes searched by this thread
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    // nodes searched by all threads, the counts of other threads lag behind by up to CHECK_INTERVAL nodes
+    pub fn total_nodes(&self) -> u64 {
+        self.shared.nodes.load(Ordering::Relaxed) + self.nodes - self.shared_nodes
+    }
+
+    pub fn max_depth(&self) -> u8 {
+        self.max_depth
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    pub fn hard_limit(&self) -> Option<Duration> {
+        self.hard_limit
+    }
+
+    pub fn soft_limit(&self) -> Option<Duration> {
+        self.soft_limit
+    }
+
+    // Called once per searched node, returns true if the search has to be aborted
+    pub fn visit_node(&mut self) -> bool {
+        self.nodes += 1;
+        if self
+            .max_nodes
+            .is_some_and(|max_nodes| self.total_nodes() >= max_nodes)
+        {
+            self.stopped = true;
+        }
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.poll();
+        }
+        self.stopped
+    }
+
+    fn poll(&mut self) {
+        self.shared
+            .nodes
+            .fetch_add(self.nodes - self.shared_nodes, Ordering::Relaxed);
+        self.shared_nodes = self.nodes;
+        if self.shared.stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+        }
+        if self.rx.as_ref().is_some_and(|rx| rx.try_recv().is_ok()) {
+            self.stopped = true;
+        }
+        if self.hard_limit.is_some_and(|limit| self.elapsed() >= limit) {
+            self.stopped = true;
+        }
+    }
+
+    // Whether the next iteration of iterative deepening should be started
+    pub fn can_start_iteration(&mut self, depth: u8) -> bool {
+        self.poll();
+        !self.stopped
+            && depth <= self.max_depth
+            && self.soft_limit.is_none_or(|limit| self.elapsed() < limit)
+    }
+
+    // In infinite mode the best move may only be sent after "stop"
+    pub fn wait_for_stop(&mut self) {
+        if self.infinite && !self.stopped {
+            if let Some(rx) = &self.rx {
+                let _ = rx.recv();
+            }
+            self.stopped = true;
+        }
+    }
+}
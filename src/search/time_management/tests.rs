@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use rstest::rstest;
+
+use crate::{
+    board::{models::Color, Board},
+    game::PositionHistory,
+    hashing::TranspTable,
+    search::{eval::smart_eval, minimax::search_iterative_deepening, search_params::SearchParams},
+};
+
+use super::{SearchLimits, TimeManager, MAX_DEPTH};
+
+#[test]
+fn test_parse_go_command() {
+    let limits = SearchLimits::from_uci(&[
+        "wtime",
+        "60000",
+        "btime",
+        "50000",
+        "winc",
+        "1000",
+        "binc",
+        "500",
+        "movestogo",
+        "20",
+    ])
+    .unwrap();
+
+    assert_eq!(
+        limits,
+        SearchLimits {
+            wtime: Some(60000),
+            btime: Some(50000),
+            winc: Some(1000),
+            binc: Some(500),
+            movestogo: Some(20),
+            ..Default::default()
+        }
+    );
+}
+
+#[rstest]
+#[case(&["movetime", "1000"], SearchLimits { movetime: Some(1000), ..Default::default() })]
+#[case(&["depth", "7"], SearchLimits::from_depth(7))]
+#[case(&["nodes", "100000"], SearchLimits { nodes: Some(100000), ..Default::default() })]
+#[case(&["mate", "3"], SearchLimits { mate: Some(3), ..Default::default() })]
+#[case(&["infinite"], SearchLimits { infinite: true, ..Default::default() })]
+#[case(&["ponder", "searchmoves", "e2e4", "depth", "2"], SearchLimits::from_depth(2))]
+fn test_parse_single_limit(#[case] arguments: &[&str], #[case] expected: SearchLimits) {
+    assert_eq!(SearchLimits::from_uci(arguments).unwrap(), expected);
+}
+
+#[rstest]
+#[case(&["wtime"])]
+#[case(&["depth", "-1"])]
+#[case(&["movetime", "soon"])]
+fn test_parse_invalid_limit(#[case] arguments: &[&str]) {
+    assert!(SearchLimits::from_uci(arguments).is_err());
+}
+
+#[test]
+fn test_budget_uses_own_clock() {
+    let limits = SearchLimits::from_uci(&["wtime", "60000", "btime", "6000"]).unwrap();
+    let white = TimeManager::new(&limits, Color::White, None);
+    let black = TimeManager::new(&limits, Color::Black, None);
+
+    assert!(white.hard_limit().unwrap() > black.hard_limit().unwrap());
+    assert!(white.soft_limit().unwrap() < white.hard_limit().unwrap());
+}
+
+#[rstest]
+#[case(&["wtime", "100000", "btime", "100000"])]
+#[case(&["wtime", "1000", "btime", "1000", "winc", "5000", "binc", "5000"])]
+#[case(&["wtime", "5000", "btime", "5000", "movestogo", "1"])]
+#[case(&["wtime", "10", "btime", "10"])]
+fn test_budget_never_exceeds_remaining_time(#[case] arguments: &[&str]) {
+    let limits = SearchLimits::from_uci(arguments).unwrap();
+    let time_manager = TimeManager::new(&limits, Color::White, None);
+    let remaining = Duration::from_millis(limits.wtime.unwrap());
+
+    assert!(time_manager.hard_limit().unwrap() < remaining);
+}
+
+#[test]
+fn test_movetime_and_infinite() {
+    let limits = SearchLimits::from_uci(&["movetime", "500", "wtime", "100000"]).unwrap();
+    let time_manager = TimeManager::new(&limits, Color::White, None);
+
+    assert_eq!(time_manager.soft_limit(), time_manager.hard_limit());
+    assert!(time_manager.hard_limit().unwrap() <= Duration::from_millis(500));
+
+    let limits = SearchLimits::from_uci(&["infinite", "depth", "3", "wtime", "100"]).unwrap();
+    let time_manager = TimeManager::new(&limits, Color::White, None);
+
+    assert_eq!(time_manager.hard_limit(), None);
+    assert_eq!(time_manager.max_depth(), MAX_DEPTH);
+}
+
+#[test]
+fn test_mate_limits_depth() {
+    let limits = SearchLimits::from_uci(&["mate", "2"]).unwrap();
+
+    assert_eq!(TimeManager::new(&limits, Color::White, None).max_depth(), 3);
+}
+
+#[test]
+fn test_node_limit_stops_search() {
+    let board = Board::default();
+    let mut time_manager = TimeManager::new(
+        &SearchLimits::from_uci(&["nodes", "5000"]).unwrap(),
+        Color::White,
+        None,
+    );
+    let mut transp_table = TranspTable::new(1);
+    search_iterative_deepening(
+        &board,
+        1,
+        1,
+        SearchParams::default(),
+        &mut smart_eval,
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+
+    assert!(time_manager.is_stopped());
+    assert_eq!(time_manager.nodes(), 5000);
+}
+
+#[test]
+fn test_node_limit_counts_all_threads() {
+    let board = Board::default();
+    let max_nodes = 50000;
+    let mut time_manager = TimeManager::new(
+        &SearchLimits::from_uci(&["nodes", &max_nodes.to_string()]).unwrap(),
+        Color::White,
+        None,
+    );
+    let mut transp_table = TranspTable::new(1);
+    search_iterative_deepening(
+        &board,
+        1,
+        4,
+        SearchParams::default(),
+        &mut smart_eval,
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+
+    assert!(time_manager.is_stopped());
+    assert!(time_manager.nodes() < max_nodes);
+    // not four times the limit, the helpers only overshoot until they see the stop
+    assert!(time_manager.total_nodes() >= max_nodes);
+    assert!(time_manager.total_nodes() < 2 * max_nodes);
+}
+
+#[test]
+fn test_iterative_deepening_finds_mate() {
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mut time_manager = TimeManager::new(&SearchLimits::from_depth(4), Color::White, None);
+    let mut transp_table = TranspTable::new(1);
+    let best_move = search_iterative_deepening(
+        &board,
+        1,
+        1,
+        SearchParams::default(),
+        &mut smart_eval,
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+
+    assert_eq!(best_move.to_san(&board), "Ra8#");
+}
+
+#[test]
+fn test_stop_message_aborts_infinite_search() {
+    let board = Board::default();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut time_manager = TimeManager::new(
+        &SearchLimits::from_uci(&["infinite"]).unwrap(),
+        Color::White,
+        Some(rx),
+    );
+    let mut transp_table = TranspTable::new(1);
+    let stopper = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        tx.send(()).unwrap();
+    });
+    search_iterative_deepening(
+        &board,
+        1,
+        1,
+        SearchParams::default(),
+        &mut smart_eval,
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+    time_manager.wait_for_stop(); // must not block, the stop message was already received
+    stopper.join().unwrap();
+
+    assert!(time_manager.is_stopped());
+    assert!(time_manager.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_helpers_run_until_stopped() {
+    let time_manager = TimeManager::new(&SearchLimits::from_depth(3), Color::White, None);
+    let mut helper = time_manager.helper();
+
+    assert!(helper.can_start_iteration(3));
+    assert!(!helper.can_start_iteration(4)); // same depth limit as the main thread
+    time_manager.stop_helpers();
+    assert!(!helper.can_start_iteration(1));
+}
+
+#[test]
+fn test_multithreaded_search_finds_mate() {
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mut time_manager = TimeManager::new(&SearchLimits::from_depth(4), Color::White, None);
+    let mut transp_table = TranspTable::new(1);
+    let best_move = search_iterative_deepening(
+        &board,
+        1,
+        4,
+        SearchParams::default(),
+        &mut smart_eval,
+        &mut transp_table,
+        &PositionHistory::from_board(&board),
+        &mut time_manager,
+    );
+
+    assert_eq!(best_move.to_san(&board), "Ra8#");
+    assert!(time_manager.total_nodes() >= time_manager.nodes());
+}
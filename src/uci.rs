@@ -1,34 +1,80 @@
-use std::thread;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
 
 use crate::{
-    board::{
-        models::{Move},
-        move_checking::apply_move,
-        Board,
-    },
-    players::{ChessPlayer, Otus, UciPlayer},
-    search::{perft},
+    board::{models::Move, Board},
+    game::Game,
+    players::{Otus, UciPlayer},
+    search::{perft, time_management::SearchLimits},
 };
 
+use self::options::{EngineOption, OPTIONS};
+
+pub mod options;
+#[cfg(test)]
+mod tests;
+
+// Jobs for the search thread, which prints "info" and "bestmove" itself
 pub enum WorkerMessage {
-    BestMove(Move),
-    Info(String),
-    Stop,
+    Go {
+        game: Box<Game>,
+        limits: SearchLimits,
+        stop_rx: Receiver<()>, // a message on this channel stops the search
+    },
+    SetOption(EngineOption), // applied between searches
+    Eval(Box<Board>),        // prints the network's score if one is loaded, else every term
+    IsReady(Sender<()>),     // answered once all earlier messages are processed
+    Quit,
 }
 
+/*
+The engine reads commands on the main thread while searches run on a long-lived worker thread,
+so "stop" and "isready" are answered during a search.
+When no search is running, "isready" waits for the worker, so that options such as a Hash resize are applied first.
+*/
 pub struct UciEngine {
-    tx: std::sync::mpsc::Sender<()>,
-    position: Board,
-    computer_agent: Otus,
+    stop_tx: Sender<()>, // stops the current search, a new channel is created for every "go"
+    game: Game,
+    worker_tx: Sender<WorkerMessage>,
+    worker: Option<JoinHandle<()>>,
+    searches: Arc<AtomicUsize>, // searches started by "go" that the worker has not finished yet
+}
+
+fn run_worker(rx: Receiver<WorkerMessage>, searches: Arc<AtomicUsize>) {
+    let mut computer_agent = Otus::new();
+    for message in rx {
+        match message {
+            WorkerMessage::Go {
+                game,
+                limits,
+                stop_rx,
+            } => {
+                computer_agent.propose_move(&game, &limits, stop_rx);
+                searches.fetch_sub(1, Ordering::SeqCst);
+            }
+            WorkerMessage::SetOption(option) => computer_agent.set_option(option),
+            WorkerMessage::Eval(board) => computer_agent.print_eval(&board),
+            WorkerMessage::IsReady(ready_tx) => {
+                let _ = ready_tx.send(());
+            }
+            WorkerMessage::Quit => break,
+        }
+    }
 }
 
-fn process_moves_list(initial_board: &Board, move_tokens: Vec<&str>) -> Board {
-    let mut board = *initial_board;
+fn process_moves_list(initial_board: &Board, move_tokens: Vec<&str>) -> Game {
+    let mut game = Game::new(*initial_board);
     for token in move_tokens {
-        let move_ = Move::from_uci_string(&board, token).expect("Invalid move syntax");
-        board = apply_move(&board, &move_).expect("Illegal move");
+        let move_ = Move::from_uci_string(game.board(), token).expect("Invalid move syntax");
+        game.play_move(&move_).expect("Illegal move");
     }
-    board
+    game
 }
 
 impl Default for UciEngine {
@@ -39,11 +85,18 @@ impl Default for UciEngine {
 
 impl UciEngine {
     pub fn new() -> Self {
-        let (tx, _) = std::sync::mpsc::channel();
+        let (stop_tx, _) = mpsc::channel();
+        let (worker_tx, worker_rx) = mpsc::channel();
+        let searches = Arc::new(AtomicUsize::new(0));
+        let worker_searches = Arc::clone(&searches);
         Self {
-            tx,
-            position: Board::default(),
-            computer_agent: Otus::new(),
+            stop_tx,
+            game: Game::default(),
+            worker_tx,
+            worker: Some(thread::spawn(move || {
+                run_worker(worker_rx, worker_searches)
+            })),
+            searches,
         }
     }
 
@@ -53,17 +106,22 @@ impl UciEngine {
         }
         match arguments[0].to_lowercase().as_str() {
             "startpos" => {
-                self.position = Board::default();
-                if arguments.len() > 1 && arguments[1].to_lowercase() == "moves" {
-                    self.position = process_moves_list(&self.position, arguments[2..].to_vec());
-                }
+                let moves = if arguments.len() > 1 && arguments[1].to_lowercase() == "moves" {
+                    arguments[2..].to_vec()
+                } else {
+                    vec![]
+                };
+                self.game = process_moves_list(&Board::default(), moves);
             }
             "fen" => {
                 let fen = arguments[1..7].join(" ");
-                self.position = Board::from_fen(&fen).expect("Invalid FEN string");
-                if arguments.len() > 7 && arguments[7].to_lowercase() == "moves" {
-                    self.position = process_moves_list(&self.position, arguments[8..].to_vec());
-                }
+                let board = Board::from_fen(&fen).expect("Invalid FEN string");
+                let moves = if arguments.len() > 7 && arguments[7].to_lowercase() == "moves" {
+                    arguments[8..].to_vec()
+                } else {
+                    vec![]
+                };
+                self.game = process_moves_list(&board, moves);
             }
             _ => {
                 // ignore
@@ -71,27 +129,66 @@ impl UciEngine {
         }
     }
 
-    fn process_go_command(&mut self, _arguments: Vec<&str>) {
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.tx = tx;
-        // TODO parse time control etc
-        thread::scope(|s| {
-            s.spawn(|| self.computer_agent.propose_move(&self.position, rx));
+    fn process_go_command(&mut self, arguments: Vec<&str>) {
+        let limits = match SearchLimits::from_uci(&arguments) {
+            Ok(limits) => limits,
+            Err(e) => {
+                println!("info string {}", e);
+                return;
+            }
+        };
+        // a search that is still running would never receive a stop on the replaced channel
+        let _ = self.stop_tx.send(());
+        let (stop_tx, stop_rx) = mpsc::channel();
+        self.stop_tx = stop_tx;
+        self.searches.fetch_add(1, Ordering::SeqCst);
+        let _ = self.worker_tx.send(WorkerMessage::Go {
+            game: Box::new(self.game.clone()),
+            limits,
+            stop_rx,
         });
     }
 
-    fn process_command(&mut self, command: &str) {
+    // Waits until the worker has processed all earlier messages, unless it is searching
+    fn wait_until_ready(&self) {
+        if self.searches.load(Ordering::SeqCst) > 0 {
+            return;
+        }
+        let (ready_tx, ready_rx) = mpsc::channel();
+        if self
+            .worker_tx
+            .send(WorkerMessage::IsReady(ready_tx))
+            .is_ok()
+        {
+            let _ = ready_rx.recv();
+        }
+    }
+
+    fn quit(&mut self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.worker_tx.send(WorkerMessage::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    // returns false if the engine should quit
+    fn process_command(&mut self, command: &str) -> bool {
         let tokens: Vec<&str> = command.split_whitespace().collect();
         if tokens.is_empty() {
-            return;
+            return true;
         }
         match tokens[0].to_lowercase().as_str() {
             "uci" => {
                 println!("id name Otus");
                 println!("id author Matthias Roshardt");
+                for option in OPTIONS {
+                    println!("{}", option);
+                }
                 println!("uciok");
             }
             "isready" => {
+                self.wait_until_ready();
                 println!("readyok");
             }
             "position" => self.process_position_command(tokens[1..].to_vec()),
@@ -99,30 +196,53 @@ impl UciEngine {
                 self.process_go_command(tokens[1..].to_vec());
             }
             "quit" => {
-                std::process::exit(0);
+                self.quit();
+                return false;
             }
-            "perft" => {
-                if tokens.len() > 1 {
-                    let depth = tokens[1].parse().expect("Invalid depth");
-                    perft::perft(&mut self.position, depth);
-                }
+            "perft" if tokens.len() > 1 => {
+                let depth = tokens[1].parse().expect("Invalid depth");
+                let mut board = *self.game.board();
+                perft::perft(&mut board, depth);
+            }
+            "eval" => {
+                // not part of UCI, for debugging
+                let board = Box::new(*self.game.board());
+                let _ = self.worker_tx.send(WorkerMessage::Eval(board));
             }
             "stop" => {
-                let _ = self.tx.send(()); // TODO if response from worker is too slow, add intermediate channel to cache latest best move
+                let _ = self.stop_tx.send(()); // the search polls for this every few thousand nodes
+            }
+            "setoption" => match EngineOption::from_setoption(&tokens[1..]) {
+                Ok(option) => {
+                    let _ = self.worker_tx.send(WorkerMessage::SetOption(option));
+                }
+                Err(e) => println!("info string {}", e),
+            },
+            "ucinewgame" => {
+                self.game = Game::default();
+                let _ = self
+                    .worker_tx
+                    .send(WorkerMessage::SetOption(EngineOption::ClearHash));
             }
             _ => {
-                // stop, ponderhit, register, setoption, debug, ucinewgame
+                // ponderhit, register, debug
             }
         }
+        true
     }
 
     pub fn run(&mut self) {
         loop {
             // commands are separated by a newline
             let mut input = String::new();
-            std::io::stdin().read_line(&mut input).unwrap();
+            if std::io::stdin().read_line(&mut input).unwrap() == 0 {
+                self.quit(); // end of input
+                return;
+            }
             for command in input.split('\n') {
-                self.process_command(command);
+                if !self.process_command(command) {
+                    return;
+                }
             }
         }
     }
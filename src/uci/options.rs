@@ -0,0 +1,199 @@
+use std::fmt;
+
+pub const DEFAULT_HASH_MB: usize = 64;
+const EMPTY_STRING: &str = "<empty>"; // the value of an empty string option
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionKind {
+    Spin { default: i64, min: i64, max: i64 },
+    Check { default: bool },
+    String { default: &'static str },
+    Button,
+}
+
+// An option advertised in the reply to "uci"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UciOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+}
+
+pub const OPTIONS: [UciOption; 12] = [
+    UciOption {
+        name: "Hash", // transposition table size in MB
+        kind: OptionKind::Spin {
+            default: DEFAULT_HASH_MB as i64,
+            min: 1,
+            max: 65536,
+        },
+    },
+    UciOption {
+        name: "Threads",
+        kind: OptionKind::Spin {
+            default: 1,
+            min: 1,
+            max: 256,
+        },
+    },
+    UciOption {
+        name: "MultiPV", // number of best lines reported
+        kind: OptionKind::Spin {
+            default: 1,
+            min: 1,
+            max: 256,
+        },
+    },
+    UciOption {
+        name: "Clear Hash",
+        kind: OptionKind::Button,
+    },
+    // parts of the search that can be switched off, see SearchParams
+    UciOption {
+        name: "NullMove",
+        kind: OptionKind::Check { default: true },
+    },
+    UciOption {
+        name: "LMR",
+        kind: OptionKind::Check { default: true },
+    },
+    UciOption {
+        name: "ReverseFutility",
+        kind: OptionKind::Check { default: true },
+    },
+    UciOption {
+        name: "Futility",
+        kind: OptionKind::Check { default: true },
+    },
+    UciOption {
+        name: "CheckExtensions",
+        kind: OptionKind::Check { default: true },
+    },
+    UciOption {
+        name: "AspirationWindows",
+        kind: OptionKind::Check { default: true },
+    },
+    UciOption {
+        name: "EvalFile", // network file for the NNUE evaluation, see search::nnue, smart_eval without
+        kind: OptionKind::String {
+            default: EMPTY_STRING,
+        },
+    },
+    UciOption {
+        name: "PstFile", // piece-square tables overriding the defaults of smart_eval, see eval/pst.txt
+        kind: OptionKind::String {
+            default: EMPTY_STRING,
+        },
+    },
+];
+
+// A validated "setoption" command
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineOption {
+    Hash(usize),
+    Threads(usize),
+    MultiPv(usize),
+    ClearHash,
+    NullMove(bool),
+    LateMoveReductions(bool),
+    ReverseFutility(bool),
+    Futility(bool),
+    CheckExtensions(bool),
+    AspirationWindows(bool),
+    EvalFile(Option<String>), // None for no file
+    PstFile(Option<String>),  // None for the default tables
+}
+
+impl fmt::Display for UciOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            OptionKind::Spin { default, min, max } => write!(
+                f,
+                "option name {} type spin default {} min {} max {}",
+                self.name, default, min, max
+            ),
+            OptionKind::Check { default } => write!(
+                f,
+                "option name {} type check default {}",
+                self.name, default
+            ),
+            OptionKind::String { default } => write!(
+                f,
+                "option name {} type string default {}",
+                self.name, default
+            ),
+            OptionKind::Button => write!(f, "option name {} type button", self.name),
+        }
+    }
+}
+
+impl UciOption {
+    fn parse_spin(&self, value: Option<&str>) -> Result<usize, String> {
+        let OptionKind::Spin { min, max, .. } = self.kind else {
+            unreachable!("{} is not a spin option", self.name)
+        };
+        let value: i64 = value
+            .and_then(|v| v.parse().ok())
+            .ok_or(format!("Invalid value for option {}", self.name))?;
+        if value < min || value > max {
+            return Err(format!(
+                "Value {} for option {} is out of range {}..={}",
+                value, self.name, min, max
+            ));
+        }
+        Ok(value as usize)
+    }
+
+    // None for an empty value
+    fn parse_string(&self, value: Option<&str>) -> Option<String> {
+        value
+            .filter(|value| *value != EMPTY_STRING)
+            .map(|value| value.to_string())
+    }
+
+    fn parse_check(&self, value: Option<&str>) -> Result<bool, String> {
+        match value.map(|v| v.to_lowercase()).as_deref() {
+            Some("true") => Ok(true),
+            Some("false") => Ok(false),
+            _ => Err(format!("Invalid value for option {}", self.name)),
+        }
+    }
+}
+
+impl EngineOption {
+    // arguments: tokens after "setoption", i.e. "name <name> [value <value>]"
+    // Option names may contain spaces and are case insensitive.
+    pub fn from_setoption(arguments: &[&str]) -> Result<EngineOption, String> {
+        if arguments.first().map(|s| s.to_lowercase()) != Some("name".to_string()) {
+            return Err("Expected setoption name <name> [value <value>]".to_string());
+        }
+        let value_index = arguments
+            .iter()
+            .position(|s| s.eq_ignore_ascii_case("value"))
+            .unwrap_or(arguments.len());
+        let name = arguments[1..value_index].join(" ");
+        let value = match arguments.get(value_index + 1..) {
+            Some(value) if !value.is_empty() => Some(value.join(" ")),
+            _ => None,
+        };
+        let option = OPTIONS
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(&name))
+            .ok_or(format!("Unknown option: {}", name))?;
+        let value = value.as_deref();
+        match option.name {
+            "Hash" => Ok(EngineOption::Hash(option.parse_spin(value)?)),
+            "Threads" => Ok(EngineOption::Threads(option.parse_spin(value)?)),
+            "MultiPV" => Ok(EngineOption::MultiPv(option.parse_spin(value)?)),
+            "Clear Hash" => Ok(EngineOption::ClearHash),
+            "NullMove" => Ok(EngineOption::NullMove(option.parse_check(value)?)),
+            "LMR" => Ok(EngineOption::LateMoveReductions(option.parse_check(value)?)),
+            "ReverseFutility" => Ok(EngineOption::ReverseFutility(option.parse_check(value)?)),
+            "Futility" => Ok(EngineOption::Futility(option.parse_check(value)?)),
+            "CheckExtensions" => Ok(EngineOption::CheckExtensions(option.parse_check(value)?)),
+            "AspirationWindows" => Ok(EngineOption::AspirationWindows(option.parse_check(value)?)),
+            "EvalFile" => Ok(EngineOption::EvalFile(option.parse_string(value))),
+            "PstFile" => Ok(EngineOption::PstFile(option.parse_string(value))),
+            _ => unreachable!("Option {} has no handler", option.name),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    sync::{atomic::Ordering, mpsc},
+    time::Duration,
+};
+
+use rstest::rstest;
+
+use crate::{game::Game, search::time_management::SearchLimits};
+
+use super::{
+    options::{EngineOption, OPTIONS},
+    UciEngine, WorkerMessage,
+};
+
+// Runs commands on a new engine, a command that blocks forever fails the test instead of hanging it
+fn run_commands(commands: fn(&mut UciEngine)) {
+    let (done_tx, done_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut engine = UciEngine::new();
+        commands(&mut engine);
+        engine.process_command("quit");
+        done_tx.send(()).unwrap();
+    });
+    done_rx
+        .recv_timeout(Duration::from_secs(30))
+        .expect("The engine is stuck");
+}
+
+#[rstest]
+#[case("name Hash value 128", EngineOption::Hash(128))]
+#[case("name hash value 1", EngineOption::Hash(1))]
+#[case("name Threads value 4", EngineOption::Threads(4))]
+#[case("name MultiPV value 3", EngineOption::MultiPv(3))]
+#[case("name Clear Hash", EngineOption::ClearHash)]
+#[case("name clear hash", EngineOption::ClearHash)]
+#[case("name NullMove value false", EngineOption::NullMove(false))]
+#[case("name lmr value TRUE", EngineOption::LateMoveReductions(true))]
+#[case(
+    "name AspirationWindows value false",
+    EngineOption::AspirationWindows(false)
+)]
+#[case(
+    "name EvalFile value nets/My Net.bin",
+    EngineOption::EvalFile(Some("nets/My Net.bin".to_string()))
+)]
+#[case("name EvalFile value <empty>", EngineOption::EvalFile(None))]
+#[case("name EvalFile", EngineOption::EvalFile(None))]
+#[case(
+    "name PstFile value tuned.txt",
+    EngineOption::PstFile(Some("tuned.txt".to_string()))
+)]
+#[case("name pstfile value <empty>", EngineOption::PstFile(None))]
+fn test_parse_setoption(#[case] arguments: &str, #[case] expected: EngineOption) {
+    let tokens: Vec<&str> = arguments.split_whitespace().collect();
+
+    assert_eq!(EngineOption::from_setoption(&tokens).unwrap(), expected);
+}
+
+#[rstest]
+#[case("name Hash")] // missing value
+#[case("name Hash value 0")] // out of range
+#[case("name Hash value many")]
+#[case("name Threads value 1000")]
+#[case("name NullMove value 1")]
+#[case("name Futility")]
+#[case("name Ponder value true")] // unknown option
+#[case("Hash value 16")] // missing name
+#[case("")]
+fn test_parse_invalid_setoption(#[case] arguments: &str) {
+    let tokens: Vec<&str> = arguments.split_whitespace().collect();
+
+    assert!(EngineOption::from_setoption(&tokens).is_err());
+}
+
+#[test]
+fn test_option_declarations() {
+    let declarations: Vec<String> = OPTIONS.iter().map(|option| option.to_string()).collect();
+
+    assert_eq!(
+        declarations,
+        vec![
+            "option name Hash type spin default 64 min 1 max 65536",
+            "option name Threads type spin default 1 min 1 max 256",
+            "option name MultiPV type spin default 1 min 1 max 256",
+            "option name Clear Hash type button",
+            "option name NullMove type check default true",
+            "option name LMR type check default true",
+            "option name ReverseFutility type check default true",
+            "option name Futility type check default true",
+            "option name CheckExtensions type check default true",
+            "option name AspirationWindows type check default true",
+            "option name EvalFile type string default <empty>",
+            "option name PstFile type string default <empty>",
+        ]
+    );
+}
+
+#[test]
+fn test_isready_waits_for_earlier_messages() {
+    run_commands(|engine| {
+        // a slow job queued without "go", like loading a network file
+        let (stop_tx, stop_rx) = mpsc::channel();
+        engine
+            .worker_tx
+            .send(WorkerMessage::Go {
+                game: Box::new(Game::default()),
+                limits: SearchLimits::from_depth(4),
+                stop_rx,
+            })
+            .unwrap();
+        engine.process_command("isready");
+
+        // the worker is done with the job and has dropped the receiver
+        assert!(stop_tx.send(()).is_err());
+    });
+}
+
+#[test]
+fn test_isready_is_answered_during_search() {
+    run_commands(|engine| {
+        engine.process_command("go infinite");
+        engine.process_command("isready");
+        assert_eq!(engine.searches.load(Ordering::SeqCst), 1);
+        engine.process_command("stop");
+    });
+}
+
+#[test]
+fn test_go_stops_running_search() {
+    run_commands(|engine| {
+        engine.process_command("go infinite");
+        engine.process_command("go depth 1");
+        engine.process_command("stop");
+        engine.process_command("isready");
+    });
+}